@@ -1,5 +1,48 @@
-use drone_config::{validate_drone_crate_config_flag, Result};
+use drone_config::{validate_drone_crate_config_flag, Layout, Result};
+use std::{env, io};
 
 fn main() -> Result<()> {
-    validate_drone_crate_config_flag(None)
+    validate_drone_crate_config_flag(None)?;
+    validate_features()?;
+    priority_bits()?;
+    Ok(())
+}
+
+/// Checks that the `security-extension` feature is supported by the target
+/// core. The `all` feature is exempt, as it is used for documentation builds.
+fn validate_features() -> Result<()> {
+    let Ok(core) = env::var("CARGO_CFG_DRONE_CORTEXM") else { return Ok(()) };
+    if env::var_os("CARGO_FEATURE_ALL").is_some()
+        || env::var_os("CARGO_FEATURE_SECURITY_EXTENSION").is_none()
+        || core.starts_with("cortexm33")
+    {
+        return Ok(());
+    }
+    let message = format!("`security-extension` feature is not supported by `{core}` core");
+    Err(io::Error::other(message).into())
+}
+
+/// Passes the number of implemented priority bits from the Drone config to
+/// `thr::PRIORITY_BITS`. Defaults to the minimum allowed by the architecture.
+fn priority_bits() -> Result<()> {
+    // Without a target core, the crate isn't built for an application.
+    let Ok(core) = env::var("CARGO_CFG_DRONE_CORTEXM") else {
+        println!("cargo:rustc-env=DRONE_CORTEXM_PRIORITY_BITS=3");
+        return Ok(());
+    };
+    let armv6m = matches!(core.as_str(), "cortexm0plus_r0p0" | "cortexm0plus_r0p1");
+    let bits = Layout::read_from_project_root()?.hardware.priority_bits;
+    let bits = match bits {
+        None if armv6m => 2,
+        None => 3,
+        Some(2) if armv6m => 2,
+        Some(bits @ 3..=8) if !armv6m => bits,
+        Some(bits) => {
+            let range = if armv6m { "exactly 2 on ARMv6-M" } else { "within 3..=8" };
+            let message = format!("`hardware.priority_bits` must be {range}, got {bits}");
+            return Err(io::Error::other(message).into());
+        }
+    };
+    println!("cargo:rustc-env=DRONE_CORTEXM_PRIORITY_BITS={bits}");
+    Ok(())
 }
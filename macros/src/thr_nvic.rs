@@ -1,10 +1,14 @@
 use heck::{ToSnakeCase, ToUpperCamelCase};
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
-use quote::{format_ident, quote};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use std::collections::HashSet;
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream, Result};
+use syn::spanned::Spanned;
 use syn::{
-    braced, parenthesized, parse_macro_input, Attribute, ExprPath, Ident, LitInt, Token, Visibility,
+    braced, parenthesized, parse_macro_input, token, Attribute, ExprPath, Ident, Lit, LitInt, Meta,
+    NestedMeta, Token, Visibility,
 };
 
 struct Input {
@@ -14,7 +18,11 @@ struct Input {
     vectors: Vectors,
     vtable: Option<Vtable>,
     init: Init,
+    info: Option<Info>,
     sv: Option<Sv>,
+    default: Option<ExprPath>,
+    storm: Option<Storm>,
+    budget: Option<ExprPath>,
     threads: Threads,
 }
 
@@ -56,17 +64,54 @@ struct Init {
     ident: Ident,
 }
 
+struct Info {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+}
+
 struct Sv {
     path: ExprPath,
 }
 
+struct Storm {
+    clock: StormClock,
+    window: LitInt,
+    hook: ExprPath,
+}
+
+enum StormClock {
+    Dwt(Ident),
+    SysTick(Ident),
+}
+
 struct Threads {
-    threads: Vec<Thread>,
+    list: Vec<Thread>,
+    int_lits: Vec<LitInt>,
+    secure_fault: Option<Ident>,
+    soft_groups: Vec<SoftGroup>,
 }
 
 enum Thread {
     Exception(ThreadSpec),
     Interrupt(u16, ThreadSpec),
+    Software(SoftRank, ThreadSpec),
+}
+
+struct SoftGroup {
+    vector: SoftVector,
+    priority: Option<LitInt>,
+}
+
+enum SoftVector {
+    PendSv,
+    Interrupt(u16),
+}
+
+#[derive(Clone, Copy)]
+struct SoftRank {
+    group: usize,
+    rank: u32,
 }
 
 struct ThreadSpec {
@@ -74,6 +119,15 @@ struct ThreadSpec {
     vis: Visibility,
     kind: ThreadKind,
     ident: Ident,
+    options: ThreadOptions,
+}
+
+#[derive(Default)]
+struct ThreadOptions {
+    priority: Option<LitInt>,
+    enabled: bool,
+    rate: Option<LitInt>,
+    budget: Option<LitInt>,
 }
 
 enum ThreadKind {
@@ -83,6 +137,7 @@ enum ThreadKind {
 }
 
 impl Parse for Input {
+    #[allow(clippy::too_many_lines)]
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let mut thr = None;
         let mut local = None;
@@ -90,11 +145,15 @@ impl Parse for Input {
         let mut vectors = None;
         let mut vtable = None;
         let mut init = None;
+        let mut info = None;
         let mut sv = None;
+        let mut default = None;
+        let mut storm = None;
+        let mut budget = None;
         let mut threads = None;
         while !input.is_empty() {
             let attrs = input.call(Attribute::parse_outer)?;
-            let ident = input.parse::<Ident>()?;
+            let ident = input.call(Ident::parse_any)?;
             input.parse::<Token![=>]>()?;
             if ident == "thread" {
                 if thr.is_none() {
@@ -132,12 +191,36 @@ impl Parse for Input {
                 } else {
                     return Err(input.error("multiple `init` specifications"));
                 }
+            } else if ident == "info" {
+                if info.is_none() {
+                    info = Some(Info::parse(input, attrs)?);
+                } else {
+                    return Err(input.error("multiple `info` specifications"));
+                }
             } else if attrs.is_empty() && ident == "supervisor" {
                 if sv.is_none() {
                     sv = Some(input.parse()?);
                 } else {
                     return Err(input.error("multiple `sv` specifications"));
                 }
+            } else if attrs.is_empty() && ident == "default" {
+                if default.is_none() {
+                    default = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `default` specifications"));
+                }
+            } else if attrs.is_empty() && ident == "storm" {
+                if storm.is_none() {
+                    storm = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `storm` specifications"));
+                }
+            } else if attrs.is_empty() && ident == "budget" {
+                if budget.is_none() {
+                    budget = Some(input.parse()?);
+                } else {
+                    return Err(input.error("multiple `budget` specifications"));
+                }
             } else if attrs.is_empty() && ident == "threads" {
                 if threads.is_none() {
                     threads = Some(input.parse()?);
@@ -158,7 +241,11 @@ impl Parse for Input {
             vectors: vectors.ok_or_else(|| input.error("missing `vectors` specification"))?,
             vtable,
             init: init.ok_or_else(|| input.error("missing `init` specification"))?,
+            info,
             sv,
+            default,
+            storm,
+            budget,
             threads: threads.ok_or_else(|| input.error("missing `threads` specification"))?,
         })
     }
@@ -218,6 +305,14 @@ impl Init {
     }
 }
 
+impl Info {
+    fn parse(input: ParseStream<'_>, attrs: Vec<Attribute>) -> Result<Self> {
+        let vis = input.parse()?;
+        let ident = input.parse()?;
+        Ok(Self { attrs, vis, ident })
+    }
+}
+
 impl Parse for Sv {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let path = input.parse()?;
@@ -225,39 +320,125 @@ impl Parse for Sv {
     }
 }
 
+impl Parse for Storm {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let input2;
+        braced!(input2 in input);
+        let mut clock_window = None;
+        let mut hook = None;
+        while !input2.is_empty() {
+            let ident = input2.parse::<Ident>()?;
+            input2.parse::<Token![=>]>()?;
+            if ident == "window" {
+                if clock_window.is_some() {
+                    return Err(input2.error("multiple `window` specifications"));
+                }
+                let clock = input2.parse::<Ident>()?;
+                let clock = if clock == "dwt" {
+                    StormClock::Dwt(clock)
+                } else if clock == "sys_tick" {
+                    StormClock::SysTick(clock)
+                } else {
+                    return Err(syn::Error::new(
+                        clock.span(),
+                        format!("unknown window clock: `{clock}`, expected `dwt` or `sys_tick`"),
+                    ));
+                };
+                let input3;
+                parenthesized!(input3 in input2);
+                let window = input3.parse::<LitInt>()?;
+                if window.base10_parse::<u32>()? == 0 {
+                    return Err(syn::Error::new(window.span(), "window must be non-zero"));
+                }
+                clock_window = Some((clock, window));
+            } else if ident == "hook" {
+                if hook.is_some() {
+                    return Err(input2.error("multiple `hook` specifications"));
+                }
+                hook = Some(input2.parse()?);
+            } else {
+                return Err(syn::Error::new(ident.span(), format!("unknown key: `{ident}`")));
+            }
+            if !input2.is_empty() {
+                input2.parse::<Token![;]>()?;
+            }
+        }
+        let (clock, window) =
+            clock_window.ok_or_else(|| input2.error("missing `window` specification"))?;
+        let hook = hook.ok_or_else(|| input2.error("missing `hook` specification"))?;
+        Ok(Self { clock, window, hook })
+    }
+}
+
 impl Parse for Threads {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let input2;
         braced!(input2 in input);
         let mut threads = Vec::new();
+        let mut int_lits = Vec::<LitInt>::new();
+        let mut secure_fault = None;
+        let mut soft_groups = Vec::new();
+        let mut exc_idents = HashSet::new();
         while !input2.is_empty() {
             let attrs = input2.call(Attribute::parse_outer)?;
             let ident = input2.parse::<Ident>()?;
-            input2.parse::<Token![=>]>()?;
-            if attrs.is_empty() && ident == "exceptions" {
+            if attrs.is_empty() && ident == "software" {
+                let group = soft_groups.len();
+                soft_groups.push(SoftGroup::parse(&input2, &mut exc_idents, &mut int_lits)?);
+                input2.parse::<Token![=>]>()?;
+                threads.extend(parse_soft_threads(&input2, group)?);
+            } else if attrs.is_empty() && ident == "exceptions" {
+                input2.parse::<Token![=>]>()?;
                 let input3;
                 braced!(input3 in input2);
                 while !input3.is_empty() {
                     let attrs = input3.call(Attribute::parse_outer)?;
                     let vis = input3.parse()?;
                     let kind = input3.parse()?;
-                    let ident = input3.parse()?;
-                    threads.push(Thread::Exception(ThreadSpec { attrs, vis, kind, ident }));
+                    let ident = input3.parse::<Ident>()?;
+                    let options = input3.parse()?;
+                    if !exc_idents.insert(ident.to_string()) {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            format!("duplicate exception `{ident}`"),
+                        ));
+                    }
+                    if ident == "secure_fault" {
+                        secure_fault = Some(ident.clone());
+                    }
+                    threads.push(Thread::Exception(ThreadSpec { attrs, vis, kind, ident, options }));
                     if !input3.is_empty() {
                         input3.parse::<Token![;]>()?;
                     }
                 }
             } else if attrs.is_empty() && ident == "interrupts" {
+                input2.parse::<Token![=>]>()?;
                 let input3;
                 braced!(input3 in input2);
                 while !input3.is_empty() {
                     let attrs = input3.call(Attribute::parse_outer)?;
-                    let num = input3.parse::<LitInt>()?.base10_parse()?;
+                    let lit = input3.parse::<LitInt>()?;
+                    let num = lit.base10_parse()?;
+                    if let Some(prev) =
+                        int_lits.iter().find(|prev| prev.base10_digits() == lit.base10_digits())
+                    {
+                        let mut err = syn::Error::new(
+                            lit.span(),
+                            format!("duplicate interrupt number {num}"),
+                        );
+                        err.combine(syn::Error::new(prev.span(), "previously declared here"));
+                        return Err(err);
+                    }
+                    int_lits.push(lit);
                     input3.parse::<Token![:]>()?;
                     let vis = input3.parse()?;
                     let kind = input3.parse()?;
                     let ident = input3.parse()?;
-                    threads.push(Thread::Interrupt(num, ThreadSpec { attrs, vis, kind, ident }));
+                    let options = input3.parse()?;
+                    threads.push(Thread::Interrupt(
+                        num,
+                        ThreadSpec { attrs, vis, kind, ident, options },
+                    ));
                     if !input3.is_empty() {
                         input3.parse::<Token![;]>()?;
                     }
@@ -269,10 +450,104 @@ impl Parse for Threads {
                 input2.parse::<Token![;]>()?;
             }
         }
-        Ok(Self { threads })
+        Ok(Self { list: threads, int_lits, secure_fault, soft_groups })
+    }
+}
+
+impl SoftGroup {
+    fn parse(
+        input: ParseStream<'_>,
+        exc_idents: &mut HashSet<String>,
+        int_lits: &mut Vec<LitInt>,
+    ) -> Result<Self> {
+        let input2;
+        parenthesized!(input2 in input);
+        let vector = if input2.peek(LitInt) {
+            let lit = input2.parse::<LitInt>()?;
+            let num = lit.base10_parse()?;
+            if let Some(prev) =
+                int_lits.iter().find(|prev| prev.base10_digits() == lit.base10_digits())
+            {
+                let mut err =
+                    syn::Error::new(lit.span(), format!("duplicate interrupt number {num}"));
+                err.combine(syn::Error::new(prev.span(), "previously declared here"));
+                return Err(err);
+            }
+            int_lits.push(lit);
+            SoftVector::Interrupt(num)
+        } else {
+            let ident = input2.parse::<Ident>()?;
+            if ident != "pend_sv" {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "virtual threads can be multiplexed only onto `pend_sv` or an interrupt",
+                ));
+            }
+            if !exc_idents.insert(ident.to_string()) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("duplicate exception `{ident}`"),
+                ));
+            }
+            SoftVector::PendSv
+        };
+        let mut priority = None;
+        while !input2.is_empty() {
+            input2.parse::<Token![,]>()?;
+            if input2.is_empty() {
+                break;
+            }
+            let ident = input2.parse::<Ident>()?;
+            if ident != "priority" {
+                return Err(syn::Error::new(ident.span(), format!("unknown option: `{ident}`")));
+            }
+            if priority.is_some() {
+                return Err(syn::Error::new(ident.span(), "multiple `priority` options"));
+            }
+            input2.parse::<Token![=]>()?;
+            priority = Some(input2.parse()?);
+        }
+        Ok(Self { vector, priority })
     }
 }
 
+fn parse_soft_threads(input: ParseStream<'_>, group: usize) -> Result<Vec<Thread>> {
+    let input2;
+    braced!(input2 in input);
+    let mut specs = Vec::new();
+    while !input2.is_empty() {
+        let attrs = input2.call(Attribute::parse_outer)?;
+        let vis = input2.parse()?;
+        let kind = input2.parse()?;
+        let ident = input2.parse::<Ident>()?;
+        let options = input2.parse::<ThreadOptions>()?;
+        if let ThreadKind::Naked(path) = &kind {
+            return Err(syn::Error::new(path.span(), "virtual threads can't be naked"));
+        }
+        if options.enabled {
+            return Err(syn::Error::new(ident.span(), "virtual threads are always enabled"));
+        }
+        let level = match &options.priority {
+            Some(priority) => priority.base10_parse::<u8>()?,
+            None => u8::MAX,
+        };
+        specs.push((level, ThreadSpec { attrs, vis, kind, ident, options }));
+        if !input2.is_empty() {
+            input2.parse::<Token![;]>()?;
+        }
+    }
+    if specs.len() > 32 {
+        return Err(input2.error("too many virtual threads for one vector, the maximum is 32"));
+    }
+    // Lower levels are higher priorities, and the declaration order breaks ties.
+    specs.sort_by_key(|&(level, _)| level);
+    Ok(specs
+        .into_iter()
+        .zip(0..)
+        .map(|((_, spec), rank)| Thread::Software(SoftRank { group, rank }, spec))
+        .collect())
+}
+
 impl Parse for ThreadKind {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         match input.fork().parse::<Ident>() {
@@ -295,30 +570,231 @@ impl Parse for ThreadKind {
     }
 }
 
+impl Parse for ThreadOptions {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mut options = Self::default();
+        if !input.peek(token::Paren) {
+            return Ok(options);
+        }
+        let input2;
+        parenthesized!(input2 in input);
+        while !input2.is_empty() {
+            let ident = input2.parse::<Ident>()?;
+            if ident == "priority" {
+                if options.priority.is_some() {
+                    return Err(syn::Error::new(ident.span(), "multiple `priority` options"));
+                }
+                input2.parse::<Token![=]>()?;
+                options.priority = Some(input2.parse()?);
+            } else if ident == "enabled" {
+                if options.enabled {
+                    return Err(syn::Error::new(ident.span(), "multiple `enabled` options"));
+                }
+                options.enabled = true;
+            } else if ident == "rate" {
+                if options.rate.is_some() {
+                    return Err(syn::Error::new(ident.span(), "multiple `rate` options"));
+                }
+                input2.parse::<Token![=]>()?;
+                let rate = input2.parse::<LitInt>()?;
+                if rate.base10_parse::<u32>()? == 0 {
+                    return Err(syn::Error::new(rate.span(), "rate must be non-zero"));
+                }
+                options.rate = Some(rate);
+            } else if ident == "budget" {
+                if options.budget.is_some() {
+                    return Err(syn::Error::new(ident.span(), "multiple `budget` options"));
+                }
+                input2.parse::<Token![=]>()?;
+                let budget = input2.parse::<LitInt>()?;
+                if budget.base10_parse::<u32>()? == 0 {
+                    return Err(syn::Error::new(budget.span(), "budget must be non-zero"));
+                }
+                options.budget = Some(budget);
+            } else {
+                return Err(syn::Error::new(ident.span(), format!("unknown option: `{ident}`")));
+            }
+            if !input2.is_empty() {
+                input2.parse::<Token![,]>()?;
+            }
+        }
+        Ok(options)
+    }
+}
+
 pub fn proc_macro(input: TokenStream) -> TokenStream {
-    let Input { thr, local, index, vectors, vtable, init, sv, threads } =
-        parse_macro_input!(input as Input);
-    let Threads { threads } = threads;
+    let Input {
+        thr,
+        local,
+        index,
+        vectors,
+        vtable,
+        init,
+        info,
+        sv,
+        default,
+        storm,
+        budget,
+        threads,
+    } = parse_macro_input!(input as Input);
+    let Threads { list: threads, int_lits, secure_fault, soft_groups } = threads;
+    if let Err(err) = check_storm(storm.as_ref(), &threads).and_then(|()| check_budget(&threads)) {
+        return err.into_compile_error().into();
+    }
     let (threads, naked_threads) = partition_threads(threads);
+    let def_checks = def_checks(&int_lits, secure_fault.as_ref(), storm.as_ref(), &threads);
     let def_thr_pool = def_thr_pool(&thr, &local, &index, &threads);
-    let def_vectors = def_vectors(&thr, &vectors, &threads, &naked_threads, &vtable);
-    let def_vtable = vtable.as_ref().map_or(quote!(), |vtable| def_vtable(&vectors, vtable));
-    let def_init = def_init(&index, &init);
-    let thr_tokens =
-        threads.iter().flat_map(|thread| def_thr_token(&sv, thread)).collect::<Vec<_>>();
+    let def_vectors = def_vectors(
+        &thr,
+        &vectors,
+        &threads,
+        &naked_threads,
+        &soft_groups,
+        &vtable,
+        default.as_ref(),
+        storm.as_ref(),
+        budget.as_ref(),
+    );
+    let def_vtable = match vtable.as_ref().map(|vtable| def_vtable(&vectors, vtable, &int_lits)) {
+        Some(Ok(def_vtable)) => def_vtable,
+        Some(Err(err)) => return err.into_compile_error().into(),
+        None => quote!(),
+    };
+    let def_vector_map = match def_vector_map(&thr, &threads) {
+        Ok(def_vector_map) => def_vector_map,
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let def_init = def_init(&index, &init, &threads, &naked_threads, &soft_groups);
+    let def_info = match info.as_ref().map(|info| def_info(info, &threads, &naked_threads)) {
+        Some(Ok(def_info)) => def_info,
+        Some(Err(err)) => return err.into_compile_error().into(),
+        None => quote!(),
+    };
+    let thr_tokens = threads
+        .iter()
+        .flat_map(|thread| def_thr_token(&sv, &soft_groups, thread))
+        .collect::<Vec<_>>();
     quote! {
+        #def_checks
         #def_thr_pool
         #def_vectors
         #def_vtable
+        #def_vector_map
         #def_init
+        #def_info
         #(#thr_tokens)*
     }
     .into()
 }
 
+fn def_checks(
+    int_lits: &[LitInt],
+    secure_fault: Option<&Ident>,
+    storm: Option<&Storm>,
+    threads: &[Thread],
+) -> TokenStream2 {
+    let mut tokens = Vec::new();
+    for lit in int_lits {
+        let msg = format!(
+            "interrupt number {} exceeds the number of interrupts supported by the core",
+            lit.base10_digits()
+        );
+        tokens.push(quote_spanned! { lit.span() =>
+            const _: () = ::core::assert!(#lit < ::drone_cortexm::thr::INT_COUNT_MAX, #msg);
+        });
+    }
+    if let Some(ident) = secure_fault {
+        tokens.push(quote_spanned! { ident.span() =>
+            const _: () = ::core::assert!(
+                ::drone_cortexm::thr::SECURITY_EXTENSION,
+                "`secure_fault` exception requires `security-extension` feature of `drone-cortexm`",
+            );
+        });
+    }
+    if let Some(Storm { clock: StormClock::Dwt(ident), .. }) = storm {
+        tokens.push(quote_spanned! { ident.span() =>
+            const _: () = ::core::assert!(
+                ::drone_cortexm::thr::CYCLE_COUNTER,
+                "`dwt` window requires DWT cycle counter, which is not available on ARMv6-M",
+            );
+        });
+    }
+    let budget = threads.iter().find_map(|thread| thread_spec(thread).options.budget.as_ref());
+    if let Some(budget) = budget {
+        tokens.push(quote_spanned! { budget.span() =>
+            const _: () = ::core::assert!(
+                ::drone_cortexm::thr::CYCLE_COUNTER,
+                "`budget` option requires DWT cycle counter, which is not available on ARMv6-M",
+            );
+        });
+    }
+    quote!(#(#tokens)*)
+}
+
+fn check_budget(threads: &[Thread]) -> Result<()> {
+    for thread in threads {
+        let ThreadSpec { kind, options: ThreadOptions { budget, .. }, .. } = thread_spec(thread);
+        if let (Some(budget), ThreadKind::Naked(_)) = (budget, kind) {
+            return Err(syn::Error::new(
+                budget.span(),
+                "`budget` option is not supported for naked threads",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn thread_spec(thread: &Thread) -> &ThreadSpec {
+    let (Thread::Exception(spec) | Thread::Interrupt(_, spec) | Thread::Software(_, spec)) = thread;
+    spec
+}
+
+fn check_storm(storm: Option<&Storm>, threads: &[Thread]) -> Result<()> {
+    for thread in threads {
+        let ThreadSpec { kind, options: ThreadOptions { rate, .. }, .. } = thread_spec(thread);
+        let Some(rate) = rate else { continue };
+        match (thread, kind) {
+            (Thread::Interrupt(..), ThreadKind::Naked(_)) => {
+                return Err(syn::Error::new(
+                    rate.span(),
+                    "`rate` option is not supported for naked threads",
+                ));
+            }
+            (Thread::Interrupt(..), _) => {}
+            _ => {
+                return Err(syn::Error::new(
+                    rate.span(),
+                    "`rate` option is supported only for interrupts",
+                ));
+            }
+        }
+        if storm.is_none() {
+            return Err(syn::Error::new(
+                rate.span(),
+                "`rate` option requires `storm` specification",
+            ));
+        }
+    }
+    if let Some(Storm { clock: StormClock::SysTick(clock), .. }) = storm {
+        let sys_tick = threads.iter().any(|thread| match thread {
+            Thread::Exception(ThreadSpec { kind, ident, .. }) => {
+                ident == "sys_tick" && !matches!(kind, ThreadKind::Naked(_))
+            }
+            _ => false,
+        });
+        if !sys_tick {
+            return Err(syn::Error::new(
+                clock.span(),
+                "`sys_tick` window requires a non-naked `sys_tick` thread",
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn partition_threads(threads: Vec<Thread>) -> (Vec<Thread>, Vec<Thread>) {
     threads.into_iter().partition(|thread| match thread {
-        Thread::Exception(spec) | Thread::Interrupt(_, spec) => {
+        Thread::Exception(spec) | Thread::Interrupt(_, spec) | Thread::Software(_, spec) => {
             let ThreadSpec { kind, .. } = spec;
             match kind {
                 ThreadKind::Inner | ThreadKind::Outer(_) => true,
@@ -328,13 +804,17 @@ fn partition_threads(threads: Vec<Thread>) -> (Vec<Thread>, Vec<Thread>) {
     })
 }
 
-#[allow(clippy::too_many_lines, clippy::cast_possible_truncation)]
+#[allow(clippy::too_many_lines, clippy::too_many_arguments, clippy::cast_possible_truncation)]
 fn def_vectors(
     thr: &Thr,
     vectors: &Vectors,
     threads: &[Thread],
     naked_threads: &[Thread],
+    soft_groups: &[SoftGroup],
     vtable: &Option<Vtable>,
+    default: Option<&ExprPath>,
+    storm: Option<&Storm>,
+    budget_hook: Option<&ExprPath>,
 ) -> TokenStream2 {
     let Thr { ident: thr_ident, .. } = thr;
     let Vectors { attrs: vectors_attrs, vis: vectors_vis, ident: vectors_ident } = vectors;
@@ -344,6 +824,10 @@ fn def_vectors(
     let mut vectors_ctor_tokens = Vec::new();
     let mut vectors_ctor_default_tokens = Vec::new();
     let mut resume_tokens = None;
+    let mut soft_arms = soft_groups.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+    // When any thread has a budget, all activations are timed to exclude
+    // preemptions from the measured cycles.
+    let timed = threads.iter().any(|thread| thread_spec(thread).options.budget.is_some());
     for (idx, thread) in threads
         .iter()
         .enumerate()
@@ -351,23 +835,40 @@ fn def_vectors(
         .chain(naked_threads.iter().map(|thread| (None, thread)))
     {
         match thread {
+            Thread::Software(SoftRank { group, rank }, spec) => {
+                let ThreadSpec { kind, .. } = spec;
+                let handler = match kind {
+                    ThreadKind::Inner => {
+                        resume_tokens.get_or_insert_with(|| def_resume(thr_ident, &resume));
+                        quote!(#resume)
+                    }
+                    ThreadKind::Outer(path) => quote!(#path),
+                    ThreadKind::Naked(_) => unreachable!(),
+                };
+                let call =
+                    def_thr_call(thr_ident, idx.unwrap(), &handler, spec, timed, budget_hook);
+                soft_arms[*group].push(quote!(#rank => { #call }));
+            }
             Thread::Exception(spec) | Thread::Interrupt(_, spec) => {
                 let ThreadSpec { kind, ident, .. } = spec;
                 let field_ident = format_ident!("{}", ident);
+                let prologue = def_storm_prologue(storm, thread, &mut tokens);
                 match kind {
                     ThreadKind::Inner => {
                         let ident = format_ident!("thr_handler_{}", idx.unwrap());
-                        resume_tokens.get_or_insert_with(|| {
-                            quote! {
-                                #[inline(never)]
-                                unsafe fn #resume(thr: &#thr_ident) {
-                                    unsafe { ::drone_core::thr::Thread::resume(thr) };
-                                }
-                            }
-                        });
+                        resume_tokens.get_or_insert_with(|| def_resume(thr_ident, &resume));
+                        let call = def_thr_call(
+                            thr_ident,
+                            idx.unwrap(),
+                            &quote!(#resume),
+                            spec,
+                            timed,
+                            budget_hook,
+                        );
                         tokens.push(quote! {
                             unsafe extern "C" fn #ident() {
-                                unsafe { <#thr_ident as ::drone_core::thr::Thread>::call(#idx, #resume) };
+                                #prologue
+                                #call
                             }
                         });
                         vectors_ctor_tokens.push(quote! {
@@ -376,9 +877,18 @@ fn def_vectors(
                     }
                     ThreadKind::Outer(path) => {
                         let ident = format_ident!("thr_handler_{}_outer", idx.unwrap());
+                        let call = def_thr_call(
+                            thr_ident,
+                            idx.unwrap(),
+                            &quote!(#path),
+                            spec,
+                            timed,
+                            budget_hook,
+                        );
                         tokens.push(quote! {
                             unsafe extern "C" fn #ident() {
-                                unsafe { <#thr_ident as ::drone_core::thr::Thread>::call(#idx, #path) };
+                                #prologue
+                                #call
                             }
                         });
                         vectors_ctor_tokens.push(quote! {
@@ -406,14 +916,70 @@ fn def_vectors(
             }
         }
     }
+    for (group, (SoftGroup { vector, .. }, arms)) in
+        soft_groups.iter().zip(soft_arms).enumerate()
+    {
+        let ident = format_ident!("thr_handler_soft_{}", group);
+        let dispatcher = format_ident!("THR_SOFT_DISPATCHER_{}", group);
+        let (field_ident, soft_vector) = match vector {
+            SoftVector::PendSv => {
+                (format_ident!("pend_sv"), quote!(::drone_cortexm::thr::SoftVector::PendSv))
+            }
+            SoftVector::Interrupt(num) => {
+                let field_ident = format_ident!("soft_int{}", num);
+                let num = *num as usize;
+                if vectors_tokens.len() < num + 1 {
+                    vectors_tokens.resize(num + 1, None);
+                }
+                vectors_tokens[num] = Some(quote! {
+                    #field_ident: ::core::option::Option<unsafe extern "C" fn()>
+                });
+                vectors_ctor_default_tokens.push(quote! {
+                    #field_ident: ::core::option::Option::None
+                });
+                let num = num as u16;
+                (field_ident, quote!(::drone_cortexm::thr::SoftVector::Int(#num)))
+            }
+        };
+        tokens.push(quote! {
+            static #dispatcher: ::drone_cortexm::thr::SoftDispatcher =
+                ::drone_cortexm::thr::SoftDispatcher::new(#soft_vector);
+
+            unsafe extern "C" fn #ident() {
+                unsafe {
+                    #dispatcher.dispatch(|rank| match rank {
+                        #(#arms,)*
+                        _ => {}
+                    });
+                }
+            }
+        });
+        vectors_ctor_tokens.push(quote! {
+            #field_ident: ::core::option::Option::Some(#ident)
+        });
+    }
+    let default_handler = default.as_ref().map(|_| format_ident!("thr_handler_default"));
+    if let (Some(ident), Some(path)) = (&default_handler, default) {
+        tokens.push(quote! {
+            unsafe extern "C" fn #ident() {
+                unsafe { ::drone_cortexm::thr::default_handler(#path) };
+            }
+        });
+    }
     let vectors_tokens = vectors_tokens
         .into_iter()
         .enumerate()
         .map(|(i, tokens)| {
             tokens.unwrap_or_else(|| {
                 let field_ident = format_ident!("_int{}", i);
-                vectors_ctor_default_tokens.push(quote! {
-                    #field_ident: ::core::option::Option::None
+                vectors_ctor_default_tokens.push(if let Some(default_handler) = &default_handler {
+                    quote! {
+                        #field_ident: ::core::option::Option::Some(#default_handler)
+                    }
+                } else {
+                    quote! {
+                        #field_ident: ::core::option::Option::None
+                    }
                 });
                 quote! {
                     #field_ident: ::core::option::Option<unsafe extern "C" fn()>
@@ -502,11 +1068,126 @@ fn def_vectors(
     }
 }
 
-fn def_vtable(vectors: &Vectors, vtable: &Vtable) -> TokenStream2 {
+fn def_storm_prologue(
+    storm: Option<&Storm>,
+    thread: &Thread,
+    tokens: &mut Vec<TokenStream2>,
+) -> TokenStream2 {
+    let Some(Storm { clock, window, hook }) = storm else {
+        return quote!();
+    };
+    match (thread, clock) {
+        (Thread::Exception(ThreadSpec { ident, .. }), StormClock::SysTick(_))
+            if ident == "sys_tick" =>
+        {
+            quote!(::drone_cortexm::thr::storm_tick();)
+        }
+        (Thread::Interrupt(num, ThreadSpec { options: ThreadOptions { rate, .. }, .. }), _) => {
+            let Some(rate) = rate else {
+                return quote!();
+            };
+            let counter = format_ident!("THR_STORM_{}", num);
+            let clock = match clock {
+                StormClock::Dwt(ident) => {
+                    quote_spanned!(ident.span() => ::drone_cortexm::thr::StormClock::Dwt)
+                }
+                StormClock::SysTick(_) => quote!(::drone_cortexm::thr::StormClock::SysTick),
+            };
+            tokens.push(quote! {
+                static #counter: ::drone_cortexm::thr::StormCounter =
+                    ::drone_cortexm::thr::StormCounter::new();
+            });
+            quote! {
+                if !unsafe {
+                    ::drone_cortexm::thr::storm_check(
+                        &#counter, #num, #clock, #window, #rate, #hook,
+                    )
+                } {
+                    return;
+                }
+            }
+        }
+        _ => quote!(),
+    }
+}
+
+fn def_thr_call(
+    thr_ident: &Ident,
+    idx: u16,
+    handler: &TokenStream2,
+    spec: &ThreadSpec,
+    timed: bool,
+    budget_hook: Option<&ExprPath>,
+) -> TokenStream2 {
+    let call = quote! {
+        unsafe { <#thr_ident as ::drone_core::thr::Thread>::call(#idx, #handler) };
+    };
+    if !timed {
+        return call;
+    }
+    let ThreadSpec { ident, options: ThreadOptions { budget, .. }, .. } = spec;
+    if budget.is_none() {
+        return quote! {
+            let span = ::drone_cortexm::thr::BudgetSpan::enter();
+            #call
+            span.exit();
+        };
+    }
+    let struct_ident = format_ident!("{}", ident.to_string().to_upper_camel_case());
+    let token = quote!(<#struct_ident as ::drone_cortexm::thr::ThrBudget>);
+    let record = quote!(unsafe { #token::meter().record(cycles, #token::BUDGET) });
+    let check = if let Some(hook) = budget_hook {
+        quote! {
+            if #record {
+                #hook(#idx, cycles);
+            }
+        }
+    } else {
+        quote!(#record;)
+    };
+    quote! {
+        let span = ::drone_cortexm::thr::BudgetSpan::enter();
+        #call
+        let cycles = span.exit();
+        #check
+    }
+}
+
+fn def_resume(thr_ident: &Ident, resume: &Ident) -> TokenStream2 {
+    quote! {
+        #[inline(never)]
+        unsafe fn #resume(thr: &#thr_ident) {
+            unsafe { ::drone_core::thr::Thread::resume(thr) };
+        }
+    }
+}
+
+fn def_vtable(vectors: &Vectors, vtable: &Vtable, int_lits: &[LitInt]) -> Result<TokenStream2> {
     let Vectors { ident: vectors_ident, .. } = vectors;
     let Vtable { attrs: vtable_attrs, vis: vtable_vis, ident: vtable_ident } = vtable;
-    quote! {
+    let mut int_count = 0;
+    for lit in int_lits {
+        int_count = int_count.max(lit.base10_parse::<usize>()? + 1);
+    }
+    // VTOR requires the table to be aligned to its size rounded up to the next
+    // power of two, and at least to 128 bytes.
+    let align = ((16 + int_count) * 4).next_power_of_two().max(128);
+    let align_attr = match repr_align(vtable_attrs)? {
+        Some((user_align, span)) if user_align < align => {
+            return Err(syn::Error::new(
+                span,
+                format!("vector table with {int_count} interrupts requires alignment of {align}"),
+            ));
+        }
+        Some(_) => quote!(),
+        None => {
+            let align = Literal::usize_unsuffixed(align);
+            quote!(#[repr(align(#align))])
+        }
+    };
+    Ok(quote! {
         #(#vtable_attrs)*
+        #align_attr
         #[repr(C)]
         #vtable_vis struct #vtable_ident {
             /// Pointer at the top of the stack.
@@ -542,6 +1223,55 @@ fn def_vtable(vectors: &Vectors, vtable: &Vtable) -> TokenStream2 {
                 }
             }
 
+            /// Replaces the handler of the interrupt `T` and returns the
+            /// previous one.
+            ///
+            /// The new handler takes effect from the next entry to the
+            /// interrupt. A currently active handler runs to completion.
+            ///
+            /// # Panics
+            ///
+            /// If the interrupt is outside of the vector table.
+            #[inline]
+            pub fn set_handler<T: ::drone_cortexm::thr::IntToken>(
+                &mut self,
+                _token: T,
+                handler: ::core::option::Option<unsafe extern "C" fn()>,
+            ) -> ::core::option::Option<unsafe extern "C" fn()> {
+                let index = 16 + <T as ::drone_cortexm::thr::IntToken>::INT_NUM as usize;
+                ::core::assert!(index <= ::core::mem::size_of::<#vectors_ident>() >> 2);
+                unsafe {
+                    ::drone_cortexm::thr::swap_vector(
+                        (self as *mut Self).cast::<usize>(),
+                        index,
+                        handler,
+                    )
+                }
+            }
+
+            /// Restores the handler of the interrupt `T` from `vectors`, and
+            /// returns the replaced one.
+            ///
+            /// # Panics
+            ///
+            /// If the interrupt is outside of the vector table.
+            #[inline]
+            pub fn restore<T: ::drone_cortexm::thr::IntToken>(
+                &mut self,
+                token: T,
+                vectors: &#vectors_ident,
+            ) -> ::core::option::Option<unsafe extern "C" fn()> {
+                let index = 16 + <T as ::drone_cortexm::thr::IntToken>::INT_NUM as usize;
+                ::core::assert!(index <= ::core::mem::size_of::<#vectors_ident>() >> 2);
+                let handler = unsafe {
+                    (vectors as *const #vectors_ident)
+                        .cast::<::core::option::Option<unsafe extern "C" fn()>>()
+                        .add(index - 1)
+                        .read()
+                };
+                self.set_handler(token, handler)
+            }
+
             /// Copies vector table bytes from `src` to `dst`.
             ///
             /// # Safety
@@ -559,7 +1289,27 @@ fn def_vtable(vectors: &Vectors, vtable: &Vtable) -> TokenStream2 {
                 }
             }
         }
+    })
+}
+
+fn repr_align(attrs: &[Attribute]) -> Result<Option<(usize, Span)>> {
+    for attr in attrs {
+        if !attr.path.is_ident("repr") {
+            continue;
+        }
+        if let Meta::List(list) = attr.parse_meta()? {
+            for nested in list.nested {
+                if let NestedMeta::Meta(Meta::List(align)) = nested {
+                    if align.path.is_ident("align") {
+                        if let Some(NestedMeta::Lit(Lit::Int(lit))) = align.nested.first() {
+                            return Ok(Some((lit.base10_parse()?, attr.span())));
+                        }
+                    }
+                }
+            }
+        }
     }
+    Ok(None)
 }
 
 fn def_thr_pool(thr: &Thr, local: &Local, index: &Index, threads: &[Thread]) -> TokenStream2 {
@@ -570,7 +1320,7 @@ fn def_thr_pool(thr: &Thr, local: &Local, index: &Index, threads: &[Thread]) ->
     let mut threads_tokens = Vec::new();
     for thread in threads {
         match thread {
-            Thread::Exception(spec) | Thread::Interrupt(_, spec) => {
+            Thread::Exception(spec) | Thread::Interrupt(_, spec) | Thread::Software(_, spec) => {
                 let ThreadSpec { attrs, vis, ident, .. } = spec;
                 threads_tokens.push(quote! {
                     #(#attrs)* #vis #ident
@@ -596,9 +1346,66 @@ fn def_thr_pool(thr: &Thr, local: &Local, index: &Index, threads: &[Thread]) ->
     }
 }
 
-fn def_init(index: &Index, init: &Init) -> TokenStream2 {
+fn def_init(
+    index: &Index,
+    init: &Init,
+    threads: &[Thread],
+    naked_threads: &[Thread],
+    soft_groups: &[SoftGroup],
+) -> TokenStream2 {
     let Init { attrs: init_attrs, vis: init_vis, ident: init_ident } = init;
     let Index { ident: index_ident, .. } = index;
+    let mut config_tokens = Vec::new();
+    for thread in threads.iter().chain(naked_threads) {
+        let (Thread::Exception(spec) | Thread::Interrupt(_, spec)) = thread else {
+            continue;
+        };
+        let ThreadSpec { ident, options: ThreadOptions { priority, enabled, .. }, .. } = spec;
+        if priority.is_none() && !enabled {
+            continue;
+        }
+        let mut config = quote!(::drone_cortexm::thr::ThrConfig::new());
+        match thread {
+            Thread::Exception(_) => {
+                let exc = format_ident!("{}", ident.to_string().to_upper_camel_case());
+                let exc = quote!(::drone_cortexm::map::thr::exc::#exc);
+                if let Some(priority) = priority {
+                    config.extend(quote!(.exc_priority::<#exc>(#priority)));
+                }
+                if *enabled {
+                    config.extend(quote!(.exc_enabled::<#exc>()));
+                }
+            }
+            Thread::Interrupt(num, _) => {
+                if let Some(priority) = priority {
+                    config.extend(quote!(.int_priority(#num, #priority)));
+                }
+                if *enabled {
+                    config.extend(quote!(.int_enabled(#num)));
+                }
+            }
+            Thread::Software(..) => unreachable!(),
+        }
+        config_tokens.push(config);
+    }
+    for SoftGroup { vector, priority } in soft_groups {
+        let mut config = quote!(::drone_cortexm::thr::ThrConfig::new());
+        match vector {
+            SoftVector::PendSv => {
+                let Some(priority) = priority else { continue };
+                config.extend(quote! {
+                    .exc_priority::<::drone_cortexm::map::thr::exc::PendSv>(#priority)
+                });
+            }
+            SoftVector::Interrupt(num) => {
+                if let Some(priority) = priority {
+                    config.extend(quote!(.int_priority(#num, #priority)));
+                }
+                config.extend(quote!(.int_enabled(#num)));
+            }
+        }
+        config_tokens.push(config);
+    }
     quote! {
         #(#init_attrs)*
         #init_vis struct #init_ident {
@@ -616,15 +1423,119 @@ fn def_init(index: &Index, init: &Init) -> TokenStream2 {
 
         unsafe impl ::drone_cortexm::thr::ThrsInitToken for #init_ident {
             type ThrTokens = #index_ident;
+
+            const THR_CONFIG: &'static [::drone_cortexm::thr::ThrConfig] = &[
+                #(#config_tokens),*
+            ];
         }
+
+        // Evaluates the thread settings table at compile time.
+        const _: &[::drone_cortexm::thr::ThrConfig] =
+            <#init_ident as ::drone_cortexm::thr::ThrsInitToken>::THR_CONFIG;
     }
 }
 
-fn def_thr_token(sv: &Option<Sv>, thread: &Thread) -> Vec<TokenStream2> {
+#[allow(clippy::cast_possible_truncation)]
+fn def_vector_map(thr: &Thr, threads: &[Thread]) -> Result<TokenStream2> {
+    let Thr { ident: thr_ident, .. } = thr;
+    let mut arms = Vec::new();
+    for (idx, thread) in threads.iter().enumerate() {
+        let idx = idx as u16;
+        let exc_num = match thread {
+            Thread::Exception(ThreadSpec { ident, .. }) => exception_number(ident)?,
+            Thread::Interrupt(num, _) => 16 + num,
+            // Virtual threads share the vector of their dispatcher.
+            Thread::Software(..) => continue,
+        };
+        arms.push(quote!(#exc_num => ::core::option::Option::Some(#idx)));
+    }
+    Ok(quote! {
+        impl ::drone_cortexm::thr::VectorMap for #thr_ident {
+            #[inline]
+            fn thr_idx(exc_num: u16) -> ::core::option::Option<u16> {
+                match exc_num {
+                    #(#arms,)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    })
+}
+
+fn def_info(info: &Info, threads: &[Thread], naked_threads: &[Thread]) -> Result<TokenStream2> {
+    let Info { attrs, vis, ident } = info;
+    let mut entries = Vec::new();
+    for thread in threads.iter().chain(naked_threads) {
+        let (Thread::Exception(spec) | Thread::Interrupt(_, spec)) = thread else {
+            continue;
+        };
+        let ThreadSpec { kind, ident, options: ThreadOptions { priority, .. }, .. } = spec;
+        let number = match thread {
+            Thread::Exception(_) => exception_number(ident)?,
+            Thread::Interrupt(num, _) => 16 + num,
+            Thread::Software(..) => unreachable!(),
+        };
+        let kind: u8 = match kind {
+            ThreadKind::Inner => 0,
+            ThreadKind::Outer(_) => 1,
+            ThreadKind::Naked(_) => 2,
+        };
+        let (flags, priority) = match priority {
+            Some(priority) => (1, priority.base10_parse::<u8>()?),
+            None => (0, 0),
+        };
+        let name = ident.to_string();
+        let name_len = u8::try_from(name.len())
+            .map_err(|_| syn::Error::new(ident.span(), "thread name is too long"))?;
+        entries.push((number, kind, flags, priority, name_len, name));
+    }
+    entries.sort_by_key(|&(number, ..)| number);
+    let count = u16::try_from(entries.len()).unwrap();
+    let mut bytes = b"DRVT".to_vec();
+    bytes.push(1);
+    bytes.extend(count.to_le_bytes());
+    for (number, kind, flags, priority, name_len, name) in entries {
+        bytes.extend(number.to_le_bytes());
+        bytes.extend([kind, flags, priority, name_len]);
+        bytes.extend(name.as_bytes());
+    }
+    let len = bytes.len();
+    Ok(quote! {
+        #(#attrs)*
+        #[used]
+        #[cfg_attr(not(feature = "host"), link_section = ".drone_vectors")]
+        #vis static #ident: [u8; #len] = [#(#bytes),*];
+    })
+}
+
+fn exception_number(ident: &Ident) -> Result<u16> {
+    Ok(match ident.to_string().as_str() {
+        "nmi" => 2,
+        "hard_fault" => 3,
+        "mem_manage" => 4,
+        "bus_fault" => 5,
+        "usage_fault" => 6,
+        "secure_fault" => 7,
+        "sv_call" => 11,
+        "debug" => 12,
+        "pend_sv" => 14,
+        "sys_tick" => 15,
+        _ => return Err(syn::Error::new(ident.span(), format!("unknown exception `{ident}`"))),
+    })
+}
+
+fn def_thr_token(
+    sv: &Option<Sv>,
+    soft_groups: &[SoftGroup],
+    thread: &Thread,
+) -> Vec<TokenStream2> {
     let mut tokens = Vec::new();
     match thread {
+        Thread::Software(soft_rank, spec) => {
+            tokens.extend(def_soft_thr_token(sv.as_ref(), soft_groups, *soft_rank, spec));
+        }
         Thread::Exception(spec) | Thread::Interrupt(_, spec) => {
-            let ThreadSpec { kind, ident, .. } = spec;
+            let ThreadSpec { kind, ident, options: ThreadOptions { priority, .. }, .. } = spec;
             match kind {
                 ThreadKind::Inner | ThreadKind::Outer(_) => {
                     let struct_ident = format_ident!("{}", ident.to_string().to_upper_camel_case());
@@ -635,6 +1546,41 @@ fn def_thr_token(sv: &Option<Sv>, thread: &Thread) -> Vec<TokenStream2> {
                             }
                         });
                     }
+                    if let Some(priority) = priority {
+                        tokens.push(quote! {
+                            impl ::drone_cortexm::thr::ThrPriority for #struct_ident {
+                                const PRIORITY: ::drone_cortexm::thr::Priority =
+                                    ::drone_cortexm::thr::Priority::from_level(#priority);
+                            }
+                        });
+                    }
+                    tokens.extend(def_thr_budget(&struct_ident, spec));
+                    if let Thread::Exception(_) = thread {
+                        tokens.push(quote! {
+                            impl ::drone_cortexm::thr::ExcToken for #struct_ident {
+                                type Exc = ::drone_cortexm::map::thr::exc::#struct_ident;
+                            }
+                        });
+                        if ident == "nmi" || ident == "pend_sv" || ident == "sys_tick" {
+                            tokens.push(quote! {
+                                impl ::drone_core::thr::ThrExec for #struct_ident {
+                                    #[inline]
+                                    fn wakeup(self) {
+                                        unsafe {
+                                            <Self as ::drone_cortexm::thr::ExcToken>::wakeup_unchecked();
+                                        }
+                                    }
+
+                                    #[inline]
+                                    fn waker(self) -> ::core::task::Waker {
+                                        unsafe {
+                                            <Self as ::drone_cortexm::thr::ExcToken>::waker_unchecked()
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                    }
                     if let Thread::Interrupt(num, _) = thread {
                         let nvic_block = format_ident!("NvicBlock{}", num / 32);
                         tokens.push(quote! {
@@ -668,3 +1614,75 @@ fn def_thr_token(sv: &Option<Sv>, thread: &Thread) -> Vec<TokenStream2> {
     }
     tokens
 }
+
+fn def_soft_thr_token(
+    sv: Option<&Sv>,
+    soft_groups: &[SoftGroup],
+    SoftRank { group, rank }: SoftRank,
+    spec: &ThreadSpec,
+) -> Vec<TokenStream2> {
+    let mut tokens = Vec::new();
+    let ThreadSpec { ident, .. } = spec;
+    let struct_ident = format_ident!("{}", ident.to_string().to_upper_camel_case());
+    let dispatcher = format_ident!("THR_SOFT_DISPATCHER_{}", group);
+    if let Some(Sv { path: sv_path }) = sv {
+        tokens.push(quote! {
+            impl ::drone_cortexm::thr::ThrSv for #struct_ident {
+                type Sv = #sv_path;
+            }
+        });
+    }
+    // Virtual threads run at the priority of their dispatcher.
+    if let Some(priority) = &soft_groups[group].priority {
+        tokens.push(quote! {
+            impl ::drone_cortexm::thr::ThrPriority for #struct_ident {
+                const PRIORITY: ::drone_cortexm::thr::Priority =
+                    ::drone_cortexm::thr::Priority::from_level(#priority);
+            }
+        });
+    }
+    tokens.extend(def_thr_budget(&struct_ident, spec));
+    tokens.push(quote! {
+        impl ::drone_cortexm::thr::SoftToken for #struct_ident {
+            const SOFT_RANK: u32 = #rank;
+
+            #[inline]
+            fn soft_thread() -> &'static ::drone_cortexm::thr::SoftThread {
+                static THREAD: ::drone_cortexm::thr::SoftThread =
+                    ::drone_cortexm::thr::SoftThread::new(&#dispatcher, #rank);
+                &THREAD
+            }
+        }
+
+        impl ::drone_core::thr::ThrExec for #struct_ident {
+            #[inline]
+            fn wakeup(self) {
+                <Self as ::drone_cortexm::thr::SoftToken>::soft_thread().wakeup();
+            }
+
+            #[inline]
+            fn waker(self) -> ::core::task::Waker {
+                <Self as ::drone_cortexm::thr::SoftToken>::soft_thread().to_waker()
+            }
+        }
+    });
+    tokens
+}
+
+fn def_thr_budget(struct_ident: &Ident, spec: &ThreadSpec) -> Option<TokenStream2> {
+    let ThreadSpec { options: ThreadOptions { budget, .. }, .. } = spec;
+    budget.as_ref().map(|budget| {
+        quote! {
+            impl ::drone_cortexm::thr::ThrBudget for #struct_ident {
+                const BUDGET: u32 = #budget;
+
+                #[inline]
+                fn meter() -> &'static ::drone_cortexm::thr::BudgetMeter {
+                    static METER: ::drone_cortexm::thr::BudgetMeter =
+                        ::drone_cortexm::thr::BudgetMeter::new();
+                    &METER
+                }
+            }
+        }
+    })
+}
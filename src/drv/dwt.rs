@@ -0,0 +1,230 @@
+//! DWT cycle comparator timer.
+
+use crate::drv::timer::{Interval, Overflow, Sleep, Stop, Timer};
+use crate::fib;
+use crate::fib::Fiber;
+use crate::map::periph;
+use crate::map::reg::{dwt, scb};
+use crate::reg::prelude::*;
+use crate::thr::prelude::*;
+use core::num::NonZeroUsize;
+use core::pin::Pin;
+use futures::stream::Stream;
+
+/// DWT cycle comparator timer driver.
+///
+/// The timer counts processor cycles with `DWT_CYCCNT`, and uses the DWT
+/// comparator 0 to raise the DebugMonitor exception on a match. All durations
+/// are in processor cycles. The thread token `I` should correspond to the
+/// `debug` exception.
+///
+/// **NOTE** When halting debug is enabled by a debugger, comparator matches
+/// halt the processor instead of raising the DebugMonitor exception.
+pub struct CycleTimer<I: ThrToken> {
+    periph: Converted,
+    int: I,
+}
+
+/// Converted DWT peripheral.
+#[allow(missing_docs)]
+pub struct Converted {
+    pub scb_demcr_trcena: scb::demcr::Trcena<Srt>,
+    pub scb_demcr_mon_pend: scb::demcr::MonPend<Crt>,
+    pub scb_demcr_mon_en: scb::demcr::MonEn<Srt>,
+    pub dwt_ctrl_cyccntena: dwt::ctrl::Cyccntena<Srt>,
+    pub dwt_cyccnt: dwt::Cyccnt<Crt>,
+    pub dwt_comp0: dwt::Comp0<Crt>,
+    pub dwt_function0: dwt::Function0<Crt>,
+}
+
+impl<I: ThrToken> Timer for CycleTimer<I> {
+    type Stop = Self;
+
+    fn sleep(&mut self, duration: u32) -> Sleep<'_, Self> {
+        let cyccnt = self.periph.dwt_cyccnt;
+        let function0 = self.periph.dwt_function0;
+        let start = self.now();
+        let fut = Box::pin(self.int.add_future(fib::new_fn(move || {
+            // Reading FUNCTION0 clears the MATCHED flag.
+            function0.load();
+            if cyccnt.load().cyccnt().wrapping_sub(start) >= duration {
+                function0.reset();
+                fib::Complete(())
+            } else {
+                fib::Yielded(())
+            }
+        })));
+        self.schedule(start.wrapping_add(duration));
+        Sleep::new(self, fut)
+    }
+
+    fn interval(&mut self, duration: u32) -> Interval<'_, Self, Result<NonZeroUsize, Overflow>> {
+        self.interval_stream(duration, |int, fib| {
+            Box::pin(int.add_pulse_try_stream(|| Err(Overflow), fib))
+        })
+    }
+
+    fn interval_skip(&mut self, duration: u32) -> Interval<'_, Self, NonZeroUsize> {
+        self.interval_stream(duration, |int, fib| Box::pin(int.add_saturating_pulse_stream(fib)))
+    }
+}
+
+impl<I: ThrToken> Stop for CycleTimer<I> {
+    fn stop(&mut self) {
+        self.periph.dwt_function0.reset();
+    }
+}
+
+impl<I: ThrToken> CycleTimer<I> {
+    /// Creates a new driver from the peripheral.
+    ///
+    /// Enables the cycle counter and the DebugMonitor exception.
+    #[inline]
+    pub fn new(periph: periph::Dwt, int: I) -> Self {
+        periph.scb_demcr_trcena.set_bit();
+        periph.dwt_ctrl_cyccntena.set_bit();
+        periph.dwt_function0.reset();
+        periph.scb_demcr_mon_en.set_bit();
+        let periph = Converted {
+            scb_demcr_trcena: periph.scb_demcr_trcena,
+            scb_demcr_mon_pend: periph.scb_demcr_mon_pend.into_copy(),
+            scb_demcr_mon_en: periph.scb_demcr_mon_en,
+            dwt_ctrl_cyccntena: periph.dwt_ctrl_cyccntena,
+            dwt_cyccnt: periph.dwt_cyccnt.into_copy(),
+            dwt_comp0: periph.dwt_comp0.into_copy(),
+            dwt_function0: periph.dwt_function0.into_copy(),
+        };
+        Self { periph, int }
+    }
+
+    /// Creates a new driver from the converted peripheral.
+    ///
+    /// # Safety
+    ///
+    /// Some of the `Crt` register tokens can be still in use.
+    #[inline]
+    pub unsafe fn from_converted(periph: Converted, int: I) -> Self {
+        Self { periph, int }
+    }
+
+    /// Releases the converted peripheral.
+    #[inline]
+    pub fn free(self) -> Converted {
+        self.periph
+    }
+
+    /// Returns the current value of the cycle counter.
+    #[inline]
+    pub fn now(&self) -> u32 {
+        self.periph.dwt_cyccnt.load().cyccnt()
+    }
+
+    fn schedule(&self, deadline: u32) {
+        schedule(
+            self.periph.dwt_cyccnt,
+            self.periph.dwt_comp0,
+            self.periph.dwt_function0,
+            self.periph.scb_demcr_mon_pend,
+            deadline,
+        );
+    }
+
+    fn interval_stream<'a, T: 'a, F>(
+        &'a mut self,
+        duration: u32,
+        f: impl FnOnce(I, F) -> Pin<Box<dyn Stream<Item = T> + Send + 'a>>,
+    ) -> Interval<'a, Self, T>
+    where
+        F: Fiber<Input = (), Yield = Option<usize>>,
+    {
+        let duration = duration.max(1);
+        let Converted {
+            scb_demcr_mon_pend: mon_pend,
+            dwt_cyccnt: cyccnt,
+            dwt_comp0: comp0,
+            dwt_function0: function0,
+            ..
+        } = self.periph;
+        let mut next = self.now().wrapping_add(duration);
+        let stream = f(
+            self.int,
+            fib::new_fn(move || {
+                function0.load();
+                let elapsed = cyccnt.load().cyccnt().wrapping_sub(next);
+                if is_negative(elapsed) {
+                    return fib::Yielded(None);
+                }
+                let pulses = elapsed / duration + 1;
+                next = next.wrapping_add(pulses.wrapping_mul(duration));
+                schedule(cyccnt, comp0, function0, mon_pend, next);
+                fib::Yielded(Some(pulses as usize))
+            }),
+        );
+        self.schedule(next);
+        Interval::new(self, stream)
+    }
+}
+
+#[allow(missing_docs)]
+impl<I: ThrToken> CycleTimer<I> {
+    #[inline]
+    pub fn int(&self) -> I {
+        self.int
+    }
+
+    #[inline]
+    pub fn cyccnt(&self) -> &dwt::Cyccnt<Srt> {
+        self.periph.dwt_cyccnt.as_sync()
+    }
+}
+
+fn schedule(
+    cyccnt: dwt::Cyccnt<Crt>,
+    comp0: dwt::Comp0<Crt>,
+    function0: dwt::Function0<Crt>,
+    mon_pend: scb::demcr::MonPend<Crt>,
+    deadline: u32,
+) {
+    comp0.store(|r| r.write_comp(deadline));
+    function0.store(cycle_match);
+    // The comparator matches only on equality, so a deadline passed during the
+    // setup would be missed.
+    if !is_negative(cyccnt.load().cyccnt().wrapping_sub(deadline)) {
+        mon_pend.set_bit();
+    }
+}
+
+#[cfg(not(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+)))]
+fn cycle_match<'a, 'b>(
+    r: &'a mut dwt::function0::Hold<'b, Crt>,
+) -> &'a mut dwt::function0::Hold<'b, Crt> {
+    // Watchpoint debug event on CYCCNT match.
+    r.set_cycmatch().write_function(0b0100)
+}
+
+#[cfg(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+))]
+fn cycle_match<'a, 'b>(
+    r: &'a mut dwt::function0::Hold<'b, Crt>,
+) -> &'a mut dwt::function0::Hold<'b, Crt> {
+    // Debug event on CYCCNT match.
+    r.write_match_type(0b0001).write_action(0b01)
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn is_negative(delta: u32) -> bool {
+    (delta as i32) < 0
+}
@@ -3,8 +3,12 @@
 //! This module provides drivers for peripherals present in each Cortex-M core.
 //! It doesn't include MCU-specific drivers.
 
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+pub mod dwt;
 pub mod sys_tick;
 pub mod timer;
 
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+pub use self::dwt::CycleTimer;
 pub use self::sys_tick::SysTick;
 pub use self::timer::Timer;
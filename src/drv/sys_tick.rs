@@ -1,24 +1,91 @@
 //! SysTick timer.
 
-use crate::drv::timer::{Interval, Overflow, Sleep, Stop, Timer};
+use crate::drv::timer::{Interval, Overflow, Reload, Sleep, Stop, Tickless, Timer};
 use crate::fib;
 use crate::fib::Fiber;
 use crate::map::periph;
 use crate::map::reg::{scb, stk};
+use crate::platform;
 use crate::reg::field::WWRegFieldBit;
 use crate::reg::prelude::*;
 use crate::thr::prelude::*;
 use core::num::NonZeroUsize;
 use core::pin::Pin;
 use core::ptr::write_volatile;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use drone_core::bitfield::Bitfield;
 use drone_core::token::Token;
 use futures::stream::Stream;
 
+/// Maximum value of the SysTick reload register.
+const RELOAD_MAX: u32 = 0x00FF_FFFF;
+
+/// Minimum reload value loaded for a single period, which keeps the reloaded
+/// counter observable before it wraps again.
+const RELOAD_ONCE_MIN: u32 = 16;
+
 /// SysTick driver.
 pub struct SysTick<I: ThrToken> {
     periph: Converted,
     int: I,
+    clock: Option<&'static TickClock>,
+}
+
+/// Monotonic tick clock for tickless idle.
+///
+/// A static instance is attached to a SysTick stream with
+/// [`SysTick::interval_tickless`] and passed to
+/// [`FutureRootExt::root_wait_tickless`](crate::thr::FutureRootExt::root_wait_tickless).
+/// When the root thread is idle, SysTick is reprogrammed to cover all ticks
+/// until [the next deadline](TickClock::set_deadline) with a single event.
+///
+/// ```no_run
+/// # use drone_core::token::Token;
+/// # mod thr {
+/// #     drone_cortexm::thr::nvic! {
+/// #         thread => pub Thr {};
+/// #         local => pub Local {};
+/// #         vectors => pub Vectors;
+/// #         index => pub Index;
+/// #         init => pub Init;
+/// #         threads => { exceptions => { pub sys_tick; }; };
+/// #     }
+/// # }
+/// use drone_cortexm::drv::sys_tick::{SysTick, TickClock};
+/// use drone_cortexm::map::cortexm_reg_tokens;
+/// use drone_cortexm::periph_sys_tick;
+/// use drone_cortexm::thr::prelude::*;
+/// use futures::prelude::*;
+///
+/// cortexm_reg_tokens! {
+///     index => Regs;
+/// }
+///
+/// static CLOCK: TickClock = TickClock::new();
+///
+/// fn handler(reg: Regs, thr: thr::Index) {
+///     let mut timer = SysTick::new(periph_sys_tick!(reg), thr.sys_tick);
+///     let mut ticks = timer.interval_tickless(72_000 - 1, &CLOCK);
+///     async {
+///         while let Some(_elapsed) = ticks.next().await {
+///             // `_elapsed` is the number of ticks since the previous item.
+///             CLOCK.set_deadline(CLOCK.now().wrapping_add(1000));
+///         }
+///     }
+///     .root_wait_tickless(&CLOCK);
+/// }
+///
+/// fn main() {
+///     handler(unsafe { Regs::take() }, unsafe { thr::Index::take() });
+/// }
+/// ```
+pub struct TickClock {
+    now: AtomicU32,
+    deadline: AtomicU32,
+    deadline_set: AtomicBool,
+    period: AtomicU32,
+    stretched: AtomicU32,
+    pending: AtomicU32,
 }
 
 /// Converted SysTick peripheral.
@@ -70,6 +137,45 @@ impl<I: ThrToken> Stop for SysTick<I> {
     fn stop(&mut self) {
         let mut ctrl_val = self.periph.stk_ctrl.load();
         self.periph.stk_ctrl.store_val(disable(&mut ctrl_val).val());
+        if let Some(clock) = self.clock.take() {
+            clock.period.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<I: ThrToken> Reload for SysTick<I> {
+    fn set_period(&mut self, duration: u32) {
+        // The counter picks up the new reload value only when it wraps to zero,
+        // so the current period completes unchanged.
+        self.periph.stk_load.store(|r| r.write_reload(duration));
+        if let Some(clock) = self.clock {
+            // A paused clock picks up the new period on resume.
+            if self.periph.stk_ctrl.load().enable() {
+                clock.period.store(duration, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        // Any write to VAL clears the counter and COUNTFLAG. The counter is
+        // reloaded from LOAD on the next clock without triggering an event.
+        self.periph.stk_val.store(|r| r.write_current(0));
+    }
+
+    fn pause(&mut self) {
+        let mut ctrl_val = self.periph.stk_ctrl.load();
+        self.periph.stk_ctrl.store_val(ctrl_val.clear_enable().val());
+        if let Some(clock) = self.clock {
+            clock.pause();
+        }
+    }
+
+    fn resume(&mut self) {
+        if let Some(clock) = self.clock {
+            clock.resume(self.periph.stk_load.load().reload());
+        }
+        let mut ctrl_val = self.periph.stk_ctrl.load();
+        self.periph.stk_ctrl.store_val(ctrl_val.set_enable().val());
     }
 }
 
@@ -84,7 +190,7 @@ impl<I: ThrToken> SysTick<I> {
             stk_load: periph.stk_load,
             stk_val: periph.stk_val,
         };
-        Self { periph, int }
+        Self { periph, int, clock: None }
     }
 
     /// Creates a new driver from the converted peripheral.
@@ -94,7 +200,7 @@ impl<I: ThrToken> SysTick<I> {
     /// Some of the `Crt` register tokens can be still in use.
     #[inline]
     pub unsafe fn from_converted(periph: Converted, int: I) -> Self {
-        Self { periph, int }
+        Self { periph, int, clock: None }
     }
 
     /// Releases the converted peripheral.
@@ -121,6 +227,24 @@ impl<I: ThrToken> SysTick<I> {
         unsafe { set_bit(&self.periph.scb_icsr_pendstclr) };
     }
 
+    /// Returns a stream of pulses that are generated on each `duration`
+    /// interval, and which can be stretched over idle periods by `clock`.
+    ///
+    /// Each item is the number of ticks elapsed since the previous item. It
+    /// can be greater than one after an idle period. See [`TickClock`] for
+    /// details.
+    pub fn interval_tickless(
+        &mut self,
+        duration: u32,
+        clock: &'static TickClock,
+    ) -> Interval<'_, Self, NonZeroUsize> {
+        clock.attach(duration);
+        self.clock = Some(clock);
+        self.interval_stream(duration, |int, ctrl| {
+            Box::pin(int.add_saturating_pulse_stream(Self::tickless_fib(ctrl, clock)))
+        })
+    }
+
     fn interval_stream<'a, T: 'a>(
         &'a mut self,
         duration: u32,
@@ -138,6 +262,203 @@ impl<I: ThrToken> SysTick<I> {
     ) -> impl Fiber<Input = (), Yield = Option<usize>, Return = T> {
         fib::new_fn(move || fib::Yielded(if ctrl.load().countflag() { Some(1) } else { None }))
     }
+
+    fn tickless_fib<T>(
+        ctrl: stk::Ctrl<Crt>,
+        clock: &'static TickClock,
+    ) -> impl Fiber<Input = (), Yield = Option<usize>, Return = T> {
+        fib::new_fn(move || {
+            fib::Yielded(if ctrl.load().countflag() { Some(clock.advance()) } else { None })
+        })
+    }
+}
+
+#[allow(clippy::new_without_default)]
+impl TickClock {
+    /// Creates a new detached tick clock.
+    pub const fn new() -> Self {
+        Self {
+            now: AtomicU32::new(0),
+            deadline: AtomicU32::new(0),
+            deadline_set: AtomicBool::new(false),
+            period: AtomicU32::new(0),
+            stretched: AtomicU32::new(0),
+            pending: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns the current value of the monotonic tick counter.
+    #[inline]
+    pub fn now(&self) -> u32 {
+        self.now.load(Ordering::Relaxed)
+    }
+
+    /// Sets a deadline at the absolute tick `tick`.
+    ///
+    /// The idle period will not be stretched beyond the deadline. If a deadline
+    /// is already set, the earliest one is kept. The deadline is cleared once
+    /// it is reached.
+    pub fn set_deadline(&self, tick: u32) {
+        platform::without_interrupts(|| {
+            let now = self.now();
+            if !self.deadline_set.load(Ordering::Relaxed)
+                || tick.wrapping_sub(now) < self.deadline.load(Ordering::Relaxed).wrapping_sub(now)
+            {
+                self.deadline.store(tick, Ordering::Relaxed);
+                self.deadline_set.store(true, Ordering::Relaxed);
+            }
+        });
+    }
+
+    /// Clears the deadline.
+    #[inline]
+    pub fn clear_deadline(&self) {
+        self.deadline_set.store(false, Ordering::Relaxed);
+    }
+
+    fn attach(&self, period: u32) {
+        platform::without_interrupts(|| {
+            self.stretched.store(0, Ordering::Relaxed);
+            self.pending.store(0, Ordering::Relaxed);
+            self.period.store(period, Ordering::Relaxed);
+        });
+    }
+
+    // Detaches the clock while the counter is stopped, so that idle periods
+    // are not stretched, as a stopped counter never takes a new reload value.
+    fn pause(&self) {
+        self.period.store(0, Ordering::Relaxed);
+    }
+
+    fn resume(&self, period: u32) {
+        self.period.store(period, Ordering::Relaxed);
+    }
+
+    // Called from the SysTick thread on each counter wrap.
+    #[allow(clippy::cast_possible_wrap)]
+    fn advance(&self) -> usize {
+        let mut ticks = 1;
+        let stretched = self.stretched.load(Ordering::Relaxed);
+        if stretched != 0 {
+            ticks = stretched;
+            self.stretched.store(0, Ordering::Relaxed);
+        }
+        ticks += self.pending.load(Ordering::Relaxed);
+        self.pending.store(0, Ordering::Relaxed);
+        let now = self.now().wrapping_add(ticks);
+        self.now.store(now, Ordering::Relaxed);
+        if self.deadline_set.load(Ordering::Relaxed)
+            && self.deadline.load(Ordering::Relaxed).wrapping_sub(now) as i32 <= 0
+        {
+            self.deadline_set.store(false, Ordering::Relaxed);
+        }
+        ticks as usize
+    }
+
+    // Returns the reload value for a single period covering up to `ticks`
+    // ticks from the counter value `current`, or `None` if it's not worth it.
+    fn begin_stretch(&self, ticks: u32, current: u32) -> Option<u32> {
+        let period = self.period.load(Ordering::Relaxed);
+        if period == 0 || self.stretched.load(Ordering::Relaxed) != 0 {
+            return None;
+        }
+        let cycles = period + 1;
+        let ticks = ticks.min((RELOAD_MAX - current) / cycles + 1);
+        if ticks < 2 {
+            return None;
+        }
+        // The counter wraps in `current` cycles. The stretched period covers the
+        // remainder of the current tick plus `ticks - 1` whole ticks, so it ends
+        // exactly on a tick boundary.
+        let load = current + (ticks - 1) * cycles - 1;
+        if load < RELOAD_ONCE_MIN {
+            return None;
+        }
+        self.stretched.store(ticks, Ordering::Relaxed);
+        Some(load)
+    }
+
+    // Returns the reload value for a single period ending on the next tick
+    // boundary of the stretched period, interrupted at the counter value
+    // `current`.
+    fn end_stretch(&self, current: u32) -> u32 {
+        let stretched = self.stretched.load(Ordering::Relaxed);
+        let cycles = self.period.load(Ordering::Relaxed) + 1;
+        // The counter wraps in `current` cycles, and the stretched period ends
+        // on a tick boundary, so the remaining cycles split into whole ticks
+        // and the remainder of the current tick.
+        let whole = current.saturating_sub(1) / cycles;
+        let mut elapsed = (stretched - 1).saturating_sub(whole);
+        let mut load = (current - whole * cycles).saturating_sub(1);
+        if load < RELOAD_ONCE_MIN {
+            // Too close to the boundary, so the next tick is merged in.
+            load += cycles;
+            elapsed += 1;
+        }
+        self.stretched.store(0, Ordering::Relaxed);
+        self.pending.store(self.pending.load(Ordering::Relaxed) + elapsed, Ordering::Relaxed);
+        load
+    }
+}
+
+impl Tickless for TickClock {
+    #[allow(clippy::cast_possible_wrap)]
+    fn next_deadline(&self) -> Option<u32> {
+        if self.deadline_set.load(Ordering::Relaxed) {
+            let ticks = self.deadline.load(Ordering::Relaxed).wrapping_sub(self.now());
+            Some(if (ticks as i32) < 0 { 0 } else { ticks })
+        } else {
+            None
+        }
+    }
+
+    fn stretch(&self, ticks: u32) -> bool {
+        platform::without_interrupts(|| {
+            if tick_pending() {
+                return false;
+            }
+            let stk_val = unsafe { stk::Val::<Srt>::take() };
+            if let Some(load) = self.begin_stretch(ticks, stk_val.load().current()) {
+                reload_once(&stk_val, load, self.period.load(Ordering::Relaxed));
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    fn unstretch(&self) {
+        platform::without_interrupts(|| {
+            // If the stretched period has elapsed, the SysTick thread accounts
+            // for it on its own. If the counter is paused, the stretched period
+            // continues after resume.
+            if self.stretched.load(Ordering::Relaxed) == 0
+                || self.period.load(Ordering::Relaxed) == 0
+                || tick_pending()
+            {
+                return;
+            }
+            let stk_val = unsafe { stk::Val::<Srt>::take() };
+            let load = self.end_stretch(stk_val.load().current());
+            reload_once(&stk_val, load, self.period.load(Ordering::Relaxed));
+        });
+    }
+}
+
+// Restarts the counter from `load`, and restores the reload value to `period`
+// for the following periods.
+fn reload_once(stk_val: &stk::Val<Srt>, load: u32, period: u32) {
+    let stk_load = unsafe { stk::Load::<Srt>::take() };
+    stk_load.store(|r| r.write_reload(load));
+    stk_val.store(|r| r.write_current(0));
+    // The counter takes the reload value on the next SysTick clock edge, which
+    // may be late with the external reference clock.
+    while stk_val.load().current() == 0 {}
+    stk_load.store(|r| r.write_reload(period));
+}
+
+fn tick_pending() -> bool {
+    unsafe { scb::Icsr::<Srt>::take() }.load().pendstset()
 }
 
 #[allow(missing_docs)]
@@ -191,3 +512,117 @@ where
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: u32 = 99;
+    const CYCLES: u32 = PERIOD + 1;
+
+    // A cycle-level model of the SysTick counter.
+    struct Counter {
+        load: u32,
+        val: u32,
+        cycle: u32,
+    }
+
+    impl Counter {
+        fn new() -> Self {
+            Self { load: PERIOD, val: 0, cycle: 0 }
+        }
+
+        fn run(&mut self, clock: &TickClock, cycles: u32) {
+            for _ in 0..cycles {
+                if self.step() {
+                    clock.advance();
+                    assert_eq!(clock.now(), self.cycle / CYCLES);
+                }
+            }
+        }
+
+        fn step(&mut self) -> bool {
+            self.cycle += 1;
+            if self.val == 0 {
+                self.val = self.load;
+                false
+            } else {
+                self.val -= 1;
+                self.val == 0
+            }
+        }
+
+        fn reload_once(&mut self, load: u32) {
+            self.load = load;
+            self.val = 0;
+            assert!(!self.step());
+            self.load = PERIOD;
+        }
+    }
+
+    fn clock() -> TickClock {
+        let clock = TickClock::new();
+        clock.period.store(PERIOD, Ordering::Relaxed);
+        clock
+    }
+
+    #[test]
+    fn stretch_wake() {
+        let clock = clock();
+        let mut counter = Counter::new();
+        counter.run(&clock, 250);
+        let load = clock.begin_stretch(10, counter.val).unwrap();
+        counter.reload_once(load);
+        counter.run(&clock, 420);
+        assert_eq!(clock.now(), 2);
+        let load = clock.end_stretch(counter.val);
+        counter.reload_once(load);
+        counter.run(&clock, 28);
+        assert_eq!(clock.now(), 7);
+        counter.run(&clock, 350);
+        assert_eq!(clock.now(), 10);
+    }
+
+    #[test]
+    fn stretch_wake_near_boundary() {
+        let clock = clock();
+        let mut counter = Counter::new();
+        counter.run(&clock, 20);
+        let load = clock.begin_stretch(5, counter.val).unwrap();
+        counter.reload_once(load);
+        counter.run(&clock, 369);
+        let load = clock.end_stretch(counter.val);
+        assert!(load >= RELOAD_ONCE_MIN);
+        counter.reload_once(load);
+        counter.run(&clock, 400);
+        assert_eq!(clock.now(), 7);
+    }
+
+    #[test]
+    fn stretch_paused() {
+        let clock = clock();
+        let mut counter = Counter::new();
+        counter.run(&clock, 150);
+        clock.pause();
+        assert!(clock.begin_stretch(10, counter.val).is_none());
+        clock.resume(PERIOD);
+        let load = clock.begin_stretch(10, counter.val).unwrap();
+        counter.reload_once(load);
+        counter.run(&clock, 1000);
+        assert_eq!(clock.now(), 11);
+    }
+
+    #[test]
+    fn stretch_elapse() {
+        let clock = clock();
+        let mut counter = Counter::new();
+        counter.run(&clock, 150);
+        let load = clock.begin_stretch(3, counter.val).unwrap();
+        counter.reload_once(load);
+        counter.run(&clock, 300);
+        assert_eq!(clock.now(), 4);
+        assert!(clock.begin_stretch(1, counter.val).is_none());
+        counter.run(&clock, 100);
+        assert_eq!(clock.now(), 5);
+    }
+}
@@ -0,0 +1,304 @@
+//! Mock timer for host-side tests.
+//!
+//! [`MockTimer`] implements [`Timer`], [`Stop`], and [`Reload`] on top of a
+//! manual clock. Time moves only when [`Clock::advance`] is called, which makes
+//! code generic over [`Timer`] testable deterministically:
+//!
+//! ```
+//! use core::pin::Pin;
+//! use core::task::{Context, Poll};
+//! use drone_cortexm::drv::timer::mock::{Call, MockTimer};
+//! use drone_cortexm::drv::timer::Timer;
+//! use futures::prelude::*;
+//! use futures::task::noop_waker_ref;
+//!
+//! let mut timer = MockTimer::new();
+//! let clock = timer.clock();
+//! let mut cx = Context::from_waker(noop_waker_ref());
+//! let mut sleep = timer.sleep(100);
+//! assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Pending);
+//! clock.advance(100);
+//! assert_eq!(Pin::new(&mut sleep).poll(&mut cx), Poll::Ready(()));
+//! drop(sleep);
+//! assert_eq!(clock.calls(), [Call::Sleep(100), Call::Stop]);
+//! ```
+
+use crate::drv::timer::{Interval, Overflow, Reload, Sleep, Stop, Timer};
+use core::num::NonZeroUsize;
+use core::task::{Poll, Waker};
+use futures::{future, stream};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// A call to [`MockTimer`] recorded by its [`Clock`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Call {
+    /// [`Timer::sleep`] was called with the given duration.
+    Sleep(u32),
+    /// [`Timer::interval`] was called with the given duration.
+    Interval(u32),
+    /// [`Timer::interval_skip`] was called with the given duration.
+    IntervalSkip(u32),
+    /// [`Stop::stop`] was called.
+    Stop,
+    /// [`Reload::set_period`] was called with the given duration.
+    SetPeriod(u32),
+    /// [`Reload::reset`] was called.
+    Reset,
+    /// [`Reload::pause`] was called.
+    Pause,
+    /// [`Reload::resume`] was called.
+    Resume,
+}
+
+/// Mock timer driven by a manual [`Clock`].
+pub struct MockTimer {
+    clock: Clock,
+}
+
+/// Manual clock of a [`MockTimer`].
+///
+/// The clock is a cheap handle, which can be cloned and used while the timer
+/// is borrowed by a [`Sleep`] future or an [`Interval`] stream.
+#[derive(Clone)]
+pub struct Clock {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    now: u64,
+    calls: Vec<Call>,
+    mode: Mode,
+    overflow: bool,
+    waker: Option<Waker>,
+}
+
+#[derive(Default)]
+enum Mode {
+    #[default]
+    Idle,
+    Sleep {
+        deadline: u64,
+    },
+    Interval {
+        period: u64,
+        next_period: Option<u64>,
+        next: u64,
+        paused: Option<u64>,
+        pulses: usize,
+    },
+}
+
+impl MockTimer {
+    /// Creates a new stopped mock timer at tick zero.
+    #[inline]
+    pub fn new() -> Self {
+        Self { clock: Clock { state: Arc::new(Mutex::new(State::default())) } }
+    }
+
+    /// Returns a handle to the manual clock of the timer.
+    #[inline]
+    pub fn clock(&self) -> Clock {
+        self.clock.clone()
+    }
+
+    fn start_interval(&mut self, call: Call, duration: u32) {
+        let mut state = self.clock.lock();
+        state.calls.push(call);
+        let period = u64::from(duration.max(1));
+        state.mode = Mode::Interval {
+            period,
+            next_period: None,
+            next: state.now + period,
+            paused: None,
+            pulses: 0,
+        };
+        state.overflow = false;
+    }
+}
+
+impl Default for MockTimer {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timer for MockTimer {
+    type Stop = Self;
+
+    fn sleep(&mut self, duration: u32) -> Sleep<'_, Self> {
+        let state = Arc::clone(&self.clock.state);
+        {
+            let mut state = self.clock.lock();
+            state.calls.push(Call::Sleep(duration));
+            state.mode = Mode::Sleep { deadline: state.now + u64::from(duration) };
+        }
+        let fut = Box::pin(future::poll_fn(move |cx| {
+            let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+            match state.mode {
+                Mode::Sleep { deadline } if state.now >= deadline => {
+                    state.mode = Mode::Idle;
+                    Poll::Ready(())
+                }
+                _ => {
+                    state.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }));
+        Sleep::new(self, fut)
+    }
+
+    fn interval(&mut self, duration: u32) -> Interval<'_, Self, Result<NonZeroUsize, Overflow>> {
+        self.start_interval(Call::Interval(duration), duration);
+        let state = Arc::clone(&self.clock.state);
+        let stream = Box::pin(stream::poll_fn(move |cx| {
+            let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+            if state.overflow {
+                state.overflow = false;
+                return Poll::Ready(Some(Err(Overflow)));
+            }
+            state.poll_pulses(cx.waker()).map(|pulses| Some(Ok(pulses)))
+        }));
+        Interval::new(self, stream)
+    }
+
+    fn interval_skip(&mut self, duration: u32) -> Interval<'_, Self, NonZeroUsize> {
+        self.start_interval(Call::IntervalSkip(duration), duration);
+        let state = Arc::clone(&self.clock.state);
+        let stream = Box::pin(stream::poll_fn(move |cx| {
+            let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.overflow = false;
+            state.poll_pulses(cx.waker()).map(Some)
+        }));
+        Interval::new(self, stream)
+    }
+}
+
+impl Stop for MockTimer {
+    fn stop(&mut self) {
+        let mut state = self.clock.lock();
+        state.calls.push(Call::Stop);
+        state.mode = Mode::Idle;
+    }
+}
+
+impl Reload for MockTimer {
+    fn set_period(&mut self, duration: u32) {
+        let mut state = self.clock.lock();
+        state.calls.push(Call::SetPeriod(duration));
+        if let Mode::Interval { next_period, .. } = &mut state.mode {
+            *next_period = Some(u64::from(duration.max(1)));
+        }
+    }
+
+    fn reset(&mut self) {
+        let mut state = self.clock.lock();
+        state.calls.push(Call::Reset);
+        let now = state.now;
+        if let Mode::Interval { period, next, paused, .. } = &mut state.mode {
+            if let Some(remaining) = paused {
+                *remaining = *period;
+            } else {
+                *next = now + *period;
+            }
+        }
+    }
+
+    fn pause(&mut self) {
+        let mut state = self.clock.lock();
+        state.calls.push(Call::Pause);
+        let now = state.now;
+        if let Mode::Interval { next, paused: paused @ None, .. } = &mut state.mode {
+            *paused = Some(*next - now);
+        }
+    }
+
+    fn resume(&mut self) {
+        let mut state = self.clock.lock();
+        state.calls.push(Call::Resume);
+        let now = state.now;
+        if let Mode::Interval { next, paused, .. } = &mut state.mode {
+            if let Some(remaining) = paused.take() {
+                *next = now + remaining;
+            }
+        }
+    }
+}
+
+impl Clock {
+    /// Returns the current tick.
+    pub fn now(&self) -> u64 {
+        self.lock().now
+    }
+
+    /// Moves the clock forward by `ticks` and wakes the pending future or
+    /// stream if its deadline is reached.
+    pub fn advance(&self, ticks: u64) {
+        let mut state = self.lock();
+        state.now += ticks;
+        let now = state.now;
+        let fired = match &mut state.mode {
+            Mode::Idle | Mode::Interval { paused: Some(_), .. } => false,
+            Mode::Sleep { deadline } => now >= *deadline,
+            Mode::Interval { period, next_period, next, paused: None, pulses } => {
+                let fired = now >= *next;
+                if fired {
+                    // A new period takes effect after the pending pulse.
+                    if let Some(next_period) = next_period.take() {
+                        *period = next_period;
+                    }
+                    let count = (now - *next) / *period + 1;
+                    *pulses = pulses.saturating_add(count as usize);
+                    *next += count * *period;
+                }
+                fired
+            }
+        };
+        if fired {
+            state.wake();
+        }
+    }
+
+    /// Makes the next poll of the stream returned by [`Timer::interval`]
+    /// produce an [`Overflow`] error.
+    pub fn overflow(&self) {
+        let mut state = self.lock();
+        state.overflow = true;
+        state.wake();
+    }
+
+    /// Returns the calls recorded so far.
+    pub fn calls(&self) -> Vec<Call> {
+        self.lock().calls.clone()
+    }
+
+    /// Removes and returns the calls recorded so far.
+    pub fn take_calls(&self) -> Vec<Call> {
+        std::mem::take(&mut self.lock().calls)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl State {
+    fn poll_pulses(&mut self, waker: &Waker) -> Poll<NonZeroUsize> {
+        if let Mode::Interval { pulses, .. } = &mut self.mode {
+            if let Some(count) = NonZeroUsize::new(*pulses) {
+                *pulses = 0;
+                return Poll::Ready(count);
+            }
+        }
+        self.waker = Some(waker.clone());
+        Poll::Pending
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
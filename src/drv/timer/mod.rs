@@ -1,5 +1,8 @@
 //! Generic timer.
 
+#[cfg(feature = "host")]
+pub mod mock;
+
 use core::fmt;
 use core::future::Future;
 use core::num::NonZeroUsize;
@@ -37,6 +40,46 @@ pub trait Stop: Send {
     fn stop(&mut self);
 }
 
+/// Timer reconfiguration handler.
+///
+/// Implemented by timers, which can change a running interval without
+/// stopping it. See [`Interval::set_period`], [`Interval::reset`],
+/// [`Interval::pause`], and [`Interval::resume`].
+pub trait Reload: Stop {
+    /// Changes the period of the running timer to `duration`.
+    ///
+    /// The current period is not affected. The new period starts after the
+    /// next timer event, so no events are missed or doubled.
+    fn set_period(&mut self, duration: u32);
+
+    /// Restarts the current period from the beginning.
+    fn reset(&mut self);
+
+    /// Pauses the timer, preserving the current counter value.
+    fn pause(&mut self);
+
+    /// Resumes the timer paused by [`Reload::pause`].
+    fn resume(&mut self);
+}
+
+/// Timer, which can stretch its periodic tick over idle periods.
+///
+/// See [`FutureRootExt::root_wait_tickless`](crate::thr::FutureRootExt::root_wait_tickless).
+pub trait Tickless: Sync {
+    /// Returns the number of ticks until the earliest deadline, or `None` if
+    /// no deadline is set.
+    fn next_deadline(&self) -> Option<u32>;
+
+    /// Reprograms the timer to generate the next event after at most `ticks`
+    /// ticks. Returns `false` if the timer was left untouched.
+    fn stretch(&self, ticks: u32) -> bool;
+
+    /// Restores the periodic tick after a stretched idle period. If the core
+    /// was woken up early by another interrupt, the monotonic time is
+    /// corrected by the number of ticks actually elapsed.
+    fn unstretch(&self);
+}
+
 /// Future created from [`Timer::sleep`].
 #[must_use]
 pub struct Sleep<'a, T: Stop> {
@@ -89,6 +132,33 @@ impl<'a, T: Stop, I> Interval<'a, T, I> {
     }
 }
 
+impl<'a, T: Reload, I> Interval<'a, T, I> {
+    /// Changes the period of the stream to `duration`, starting from the next
+    /// pulse.
+    #[inline]
+    pub fn set_period(mut self: Pin<&mut Self>, duration: u32) {
+        self.stop.set_period(duration);
+    }
+
+    /// Restarts the current period of the stream from the beginning.
+    #[inline]
+    pub fn reset(mut self: Pin<&mut Self>) {
+        self.stop.reset();
+    }
+
+    /// Pauses the stream without stopping the timer.
+    #[inline]
+    pub fn pause(mut self: Pin<&mut Self>) {
+        self.stop.pause();
+    }
+
+    /// Resumes the stream paused by [`Interval::pause`].
+    #[inline]
+    pub fn resume(mut self: Pin<&mut Self>) {
+        self.stop.resume();
+    }
+}
+
 impl<'a, T: Stop, I> Stream for Interval<'a, T, I> {
     type Item = I;
 
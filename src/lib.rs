@@ -27,6 +27,11 @@
 //! Rust target triple and `drone_cortexm` Rust flag should be set at the
 //! application level according to this table.
 //!
+//! The number of priority bits implemented by the MCU can be set with the
+//! `priority_bits` key in the `[hardware]` section of the Drone config. It must
+//! be 2 for ARMv6-M, and within 3..=8 for ARMv7-M and ARMv8-M, where it
+//! defaults to 3.
+//!
 //! # Documentation
 //!
 //! - [Drone Book](https://book.drone-os.com/)
@@ -0,0 +1,31 @@
+//! Data Watchpoint and Trace cycle comparator.
+
+use drone_core::periph;
+
+periph::singular! {
+    /// Extracts DWT cycle comparator register tokens.
+    pub macro periph_dwt;
+
+    /// DWT cycle comparator peripheral.
+    pub struct Dwt;
+
+    crate::map::reg;
+    crate::map::periph::dwt;
+
+    SCB {
+        DEMCR {
+            TRCENA;
+            MON_PEND;
+            MON_EN;
+        }
+    }
+
+    DWT {
+        CTRL {
+            CYCCNTENA;
+        }
+        CYCCNT;
+        COMP0;
+        FUNCTION0;
+    }
+}
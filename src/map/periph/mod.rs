@@ -1,5 +1,7 @@
 //! Core ARM Cortex-M peripheral mappings.
 
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+pub mod dwt;
 #[cfg(feature = "floating-point-unit")]
 pub mod fpu;
 #[cfg(feature = "memory-protection-unit")]
@@ -7,6 +9,8 @@ pub mod mpu;
 pub mod sys_tick;
 pub mod thr;
 
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+pub use self::dwt::Dwt;
 #[cfg(feature = "floating-point-unit")]
 pub use self::fpu::Fpu;
 #[cfg(feature = "memory-protection-unit")]
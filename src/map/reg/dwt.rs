@@ -69,3 +69,81 @@ reg! {
         };
     };
 }
+
+reg! {
+    /// Comparator Register 0.
+    pub DWT COMP0 => {
+        address => 0xE000_1020;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RReg WReg };
+        fields => {
+            /// Reference value for comparison.
+            COMP => { offset => 0; width => 32; traits => { RRRegField WWRegField } };
+        };
+    };
+}
+
+#[cfg(not(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+)))]
+reg! {
+    /// Function Register 0.
+    pub DWT FUNCTION0 => {
+        address => 0xE000_1028;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RReg WReg };
+        fields => {
+            /// Comparator match. Reading the register clears this bit.
+            MATCHED => { offset => 24; width => 1; traits => { RRRegField RoRRegField } };
+            /// Size of the data comparison.
+            DATAVSIZE => { offset => 10; width => 2; traits => { RRRegField WWRegField } };
+            /// Indicates whether a second linked comparator is supported.
+            LNK1ENA => { offset => 9; width => 1; traits => { RRRegField RoRRegField } };
+            /// Enables data value comparison.
+            DATAVMATCH => { offset => 8; width => 1; traits => { RRRegField WWRegField } };
+            /// Enables cycle count comparison.
+            CYCMATCH => { offset => 7; width => 1; traits => { RRRegField WWRegField } };
+            /// Enables generation of data trace address packets.
+            EMITRANGE => { offset => 5; width => 1; traits => { RRRegField WWRegField } };
+            /// Selects the action taken on comparator match.
+            FUNCTION => { offset => 0; width => 4; traits => { RRRegField WWRegField } };
+        };
+    };
+}
+
+#[cfg(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+))]
+reg! {
+    /// Function Register 0.
+    pub DWT FUNCTION0 => {
+        address => 0xE000_1028;
+        size => 0x20;
+        reset => 0x0000_0000;
+        traits => { RReg WReg };
+        fields => {
+            /// Identifies the capabilities of the comparator.
+            ID => { offset => 27; width => 5; traits => { RRRegField RoRRegField } };
+            /// Comparator match. Reading the register clears this bit.
+            MATCHED => { offset => 24; width => 1; traits => { RRRegField RoRRegField } };
+            /// Size of the data comparison.
+            DATAVSIZE => { offset => 10; width => 2; traits => { RRRegField WWRegField } };
+            /// Action on match.
+            ACTION => { offset => 4; width => 2; traits => { RRRegField WWRegField } };
+            /// Match type (the `MATCH` field).
+            MATCH_TYPE => { offset => 0; width => 4; traits => { RRRegField WWRegField } };
+        };
+    };
+}
@@ -33,7 +33,7 @@ reg::tokens! {
 
     /// Data watchpoint and trace.
     pub mod DWT {
-        CYCCNT;
+        CTRL; CYCCNT; COMP0; FUNCTION0;
     }
 
     /// Instrumentation trace macrocell.
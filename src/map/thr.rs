@@ -43,3 +43,42 @@ nvic_block_cortexm33!(NvicBlock12, 12, "NVIC register block 12.");
 nvic_block_cortexm33!(NvicBlock13, 13, "NVIC register block 13.");
 nvic_block_cortexm33!(NvicBlock14, 14, "NVIC register block 14.");
 nvic_block_cortexm33!(NvicBlock15, 15, "NVIC register block 15.");
+
+/// System exception markers.
+///
+/// Each exception thread token generated by [`thr::nvic!`](crate::thr::nvic)
+/// refers to one of these types through
+/// [`ExcToken::Exc`](crate::thr::ExcToken::Exc). The set of capabilities
+/// implemented by a marker depends on the core.
+pub mod exc {
+    use crate::thr::Exception;
+
+    macro_rules! exception {
+        ($(#[$attr:meta])* $name:ident, $number:expr, $doc:expr) => {
+            #[doc = $doc]
+            $(#[$attr])*
+            pub enum $name {}
+
+            $(#[$attr])*
+            impl Exception for $name {
+                const EXC_NUM: u8 = $number;
+            }
+        };
+    }
+
+    exception!(Nmi, 2, "Non maskable interrupt.");
+    exception!(HardFault, 3, "All classes of fault.");
+    exception!(MemManage, 4, "Memory management.");
+    exception!(BusFault, 5, "Pre-fetch fault, memory access fault.");
+    exception!(UsageFault, 6, "Undefined instruction or illegal state.");
+    exception!(
+        #[cfg(feature = "security-extension")]
+        SecureFault,
+        7,
+        "Security check violation."
+    );
+    exception!(SvCall, 11, "System service call via SWI instruction.");
+    exception!(Debug, 12, "Monitor.");
+    exception!(PendSv, 14, "Pendable request for system service.");
+    exception!(SysTick, 15, "System tick timer.");
+}
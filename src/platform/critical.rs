@@ -0,0 +1,47 @@
+//! Critical sections.
+//!
+//! [`with_priority`] masks only the exceptions which priority is not higher
+//! than a ceiling, unlike `PRIMASK` which masks all configurable exceptions.
+
+use super::without_interrupts;
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+use crate::platform::regs;
+use crate::thr::Priority;
+
+/// Runs the closure `f` with the execution priority raised to at least
+/// `ceiling`.
+///
+/// On ARMv7-M and ARMv8-M Mainline the priority is raised with `BASEPRI_MAX`,
+/// which never lowers the current boosting, and the previous `BASEPRI` value is
+/// restored afterwards. Exceptions with priorities higher than `ceiling` can
+/// still preempt `f`.
+///
+/// `BASEPRI` can't mask exceptions with [`Priority::HIGHEST`], so for this
+/// ceiling, as well as on ARMv6-M, all maskable interrupts are disabled with
+/// `PRIMASK` instead.
+///
+/// ```no_run
+/// use drone_cortexm::platform::critical;
+/// use drone_cortexm::thr::Priority;
+///
+/// let value = critical::with_priority(Priority::from_level(2), || 42);
+/// assert_eq!(value, 42);
+/// ```
+#[inline]
+pub fn with_priority<R>(ceiling: Priority, f: impl FnOnce() -> R) -> R {
+    #[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+    {
+        let _ = ceiling;
+        without_interrupts(f)
+    }
+    #[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+    if ceiling == Priority::HIGHEST {
+        without_interrupts(f)
+    } else {
+        let basepri = regs::basepri();
+        regs::set_basepri_max(ceiling.to_raw());
+        let result = f();
+        unsafe { regs::set_basepri(basepri) };
+        result
+    }
+}
@@ -0,0 +1,36 @@
+//! Emulation of `PRIMASK` critical sections on the host.
+//!
+//! A single core is emulated, so host threads disabling interrupts exclude
+//! each other. The emulated `PRIMASK` register is set while a thread is inside
+//! a critical section.
+
+use super::regs;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+static MASKED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static OWNER: Cell<bool> = const { Cell::new(false) };
+}
+
+pub(super) fn save_and_disable_interrupts() -> u32 {
+    if OWNER.with(Cell::get) {
+        return 1;
+    }
+    while MASKED.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+        thread::yield_now();
+    }
+    OWNER.with(|owner| owner.set(true));
+    unsafe { regs::set_primask(true) };
+    0
+}
+
+pub(super) fn restore_interrupts(status: u32) {
+    if status & 1 == 0 {
+        unsafe { regs::set_primask(false) };
+        OWNER.with(|owner| owner.set(false));
+        MASKED.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,357 @@
+//! ARM Cortex-M CPU management.
+
+#![cfg_attr(feature = "host", allow(dead_code, unused_variables, unreachable_code))]
+
+pub mod critical;
+pub mod regs;
+
+#[cfg(feature = "host")]
+mod host;
+
+#[cfg(not(feature = "host"))]
+use core::arch::asm;
+#[doc(no_inline)]
+pub use drone_core::platform::*;
+
+/// Waits for interrupt.
+///
+/// It is a hint instruction. It suspends execution, in the lowest power state
+/// available consistent with a fast wakeup without the need for software
+/// restoration, until a reset, asynchronous exception or other event occurs.
+#[inline]
+pub fn wait_for_int() {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!("wfi", options(nomem, nostack, preserves_flags));
+    }
+}
+
+/// Waits for event.
+///
+/// It is a hint instruction. If the Event Register is clear, it suspends
+/// execution in the lowest power state available consistent with a fast wakeup
+/// without the need for software restoration, until a reset, exception or other
+/// event occurs.
+///
+/// See also [`send_event`].
+#[inline]
+pub fn wait_for_event() {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!("wfe", options(nomem, nostack, preserves_flags));
+    }
+}
+
+/// Sends an event.
+///
+/// It is a hint instruction. It causes an event to be signaled to all CPUs
+/// within the multiprocessor system.
+///
+/// See also [`wait_for_event`].
+#[inline]
+pub fn send_event() {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!("sev", options(nomem, nostack, preserves_flags));
+    }
+}
+
+/// Spins the `cycles` number of processor cycles in a tight loop.
+///
+/// The loop assumes 3 cycles per iteration. See [`delay_cycles`] for a delay
+/// with a documented accuracy.
+#[inline(always)]
+pub fn spin(cycles: u32) {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!(
+            "0:  subs {0}, {0}, #3",
+            "    bhi 0b",
+            inlateout(reg) cycles => _,
+            options(nomem, nostack),
+        );
+    }
+}
+
+/// Busy-waits for at least `cycles` processor cycles.
+///
+/// If the DWT cycle counter is present and enabled (`DWT_CTRL.CYCCNTENA` is
+/// set, e.g. by [`drv::dwt::CycleTimer`](crate::drv::dwt::CycleTimer)), the
+/// delay is measured with `DWT_CYCCNT`, and overshoots by at most one polling
+/// iteration of about 10 cycles. Otherwise a loop is used, which assumes the
+/// fastest iteration documented for the core, so that the delay is never
+/// shorter than requested. Interrupts and flash wait states extend the delay,
+/// but never shorten it.
+///
+/// | Core flag           | Method           | Loop iteration | Loop delay (no wait states) |
+/// |---------------------|------------------|----------------|-----------------------------|
+/// | `cortexm0plus_r0p*` | loop             | 3 cycles       | `cycles` + 0..2             |
+/// | `cortexm3_r*`       | `CYCCNT` or loop | 3..5 cycles    | up to 5/3 × `cycles` + 2    |
+/// | `cortexm4_r0p*`     | `CYCCNT` or loop | 3..5 cycles    | up to 5/3 × `cycles` + 2    |
+/// | `cortexm4f_r0p*`    | `CYCCNT` or loop | 3..5 cycles    | up to 5/3 × `cycles` + 2    |
+/// | `cortexm33_r0p*`    | `CYCCNT` or loop | 2+ cycles      | unspecified                 |
+/// | `cortexm33f_r0p*`   | `CYCCNT` or loop | 2+ cycles      | unspecified                 |
+///
+/// An iteration is a `SUBS` and a taken conditional branch, which takes a
+/// fixed 2 cycles on Cortex-M0+, and 2 to 4 cycles on Cortex-M3 and
+/// Cortex-M4. Cortex-M33 doesn't document its branch timing, so the loop
+/// assumes a single-cycle branch there, and may take a few times longer than
+/// requested.
+#[inline]
+pub fn delay_cycles(cycles: u32) {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    {
+        #[cfg(not(any(
+            drone_cortexm = "cortexm0plus_r0p0",
+            drone_cortexm = "cortexm0plus_r0p1"
+        )))]
+        if cyccnt_enabled() {
+            let start = cyccnt();
+            while cyccnt().wrapping_sub(start) < cycles {}
+            return;
+        }
+        delay_loop(cycles);
+    }
+}
+
+/// Busy-waits for at least `us` microseconds at the processor frequency of
+/// `cpu_freq` Hz.
+///
+/// See [`delay_cycles`] for the accuracy.
+#[inline]
+pub fn delay_us(us: u32, cpu_freq: u32) {
+    delay_long(cycles_for(u64::from(us), cpu_freq, 1_000_000));
+}
+
+/// Busy-waits for at least `ns` nanoseconds at the processor frequency of
+/// `cpu_freq` Hz.
+///
+/// See [`delay_cycles`] for the accuracy.
+#[inline]
+pub fn delay_ns(ns: u32, cpu_freq: u32) {
+    delay_long(cycles_for(u64::from(ns), cpu_freq, 1_000_000_000));
+}
+
+fn cycles_for(time: u64, cpu_freq: u32, units_per_sec: u64) -> u64 {
+    // Rounds up to never produce a shorter delay.
+    (time * u64::from(cpu_freq) + units_per_sec - 1) / units_per_sec
+}
+
+fn delay_long(mut cycles: u64) {
+    // Keeps each chunk far from the `CYCCNT` wrap-around.
+    const CHUNK: u32 = 1 << 31;
+    while cycles > u64::from(CHUNK) {
+        delay_cycles(CHUNK);
+        cycles -= u64::from(CHUNK);
+    }
+    delay_cycles(cycles as u32);
+}
+
+/// The minimum number of cycles per iteration of [`delay_loop`].
+#[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+const LOOP_CYCLES: u32 = 3;
+/// The minimum number of cycles per iteration of [`delay_loop`].
+#[cfg(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+))]
+const LOOP_CYCLES: u32 = 2;
+/// The minimum number of cycles per iteration of [`delay_loop`].
+#[cfg(not(any(
+    drone_cortexm = "cortexm0plus_r0p0",
+    drone_cortexm = "cortexm0plus_r0p1",
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+)))]
+const LOOP_CYCLES: u32 = 3;
+
+#[inline(always)]
+fn delay_loop(cycles: u32) {
+    // The last iteration is shorter by a not-taken branch, so an extra
+    // iteration is added to never produce a shorter delay.
+    let iterations = cycles / LOOP_CYCLES + 1;
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!(
+            "0:  subs {0}, {0}, #1",
+            "    bhi 0b",
+            inlateout(reg) iterations => _,
+            options(nomem, nostack),
+        );
+    }
+}
+
+#[cfg(not(any(
+    feature = "host",
+    drone_cortexm = "cortexm0plus_r0p0",
+    drone_cortexm = "cortexm0plus_r0p1"
+)))]
+fn cyccnt_enabled() -> bool {
+    const DWT_CTRL: usize = 0xE000_1000;
+    const NOCYCCNT: u32 = 1 << 25;
+    const CYCCNTENA: u32 = 1 << 0;
+    let ctrl = unsafe { core::ptr::read_volatile(DWT_CTRL as *const u32) };
+    ctrl & (NOCYCCNT | CYCCNTENA) == CYCCNTENA
+}
+
+/// Reads `DWT_CYCCNT`.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+pub(crate) fn dwt_cycles() -> u32 {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    cyccnt()
+}
+
+#[cfg(not(any(
+    feature = "host",
+    drone_cortexm = "cortexm0plus_r0p0",
+    drone_cortexm = "cortexm0plus_r0p1"
+)))]
+fn cyccnt() -> u32 {
+    const DWT_CYCCNT: usize = 0xE000_1004;
+    unsafe { core::ptr::read_volatile(DWT_CYCCNT as *const u32) }
+}
+
+/// Enables the FPU.
+///
+/// The FPU is disabled from reset. You must enable it before you can use any
+/// floating-point instructions.
+///
+/// # Safety
+///
+/// * The processor must be in privileged mode
+/// * The function rewrites contents of FPU_CPACR register without taking into
+///   account register tokens
+#[cfg(feature = "floating-point-unit")]
+#[inline]
+pub unsafe fn fpu_init(full_access: bool) {
+    const FPU_CPACR: usize = 0xE000_ED88;
+    unsafe {
+        core::ptr::write_volatile(
+            FPU_CPACR as *mut u32,
+            if full_access {
+                0xF // full access
+            } else {
+                0x5 // privileged access only
+            } << 20,
+        );
+    }
+}
+
+/// Runs the closure `f` with all maskable interrupts disabled.
+#[inline]
+pub(crate) fn without_interrupts<R>(f: impl FnOnce() -> R) -> R {
+    let status = drone_save_and_disable_interrupts();
+    let result = f();
+    drone_restore_interrupts(status);
+    result
+}
+
+#[no_mangle]
+extern "C" fn drone_save_and_disable_interrupts() -> u32 {
+    #[cfg(feature = "host")]
+    return host::save_and_disable_interrupts();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        let status: u32;
+        asm!(
+            "mrs {status}, PRIMASK",
+            "cpsid i",
+            status = out(reg) status,
+            options(nomem, nostack, preserves_flags),
+        );
+        status
+    }
+}
+
+#[no_mangle]
+extern "C" fn drone_restore_interrupts(status: u32) {
+    #[cfg(feature = "host")]
+    return host::restore_interrupts(status);
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!(
+            "msr PRIMASK, {status}",
+            status = in(reg) status,
+            options(nomem, nostack, preserves_flags),
+        );
+    }
+}
+
+#[no_mangle]
+extern "C" fn drone_reset() -> ! {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        use crate::map::reg::scb;
+        use crate::reg::prelude::*;
+        use drone_core::token::Token;
+        regs::dmb();
+        asm!("cpsid f", options(nomem, nostack, preserves_flags));
+        scb::Aircr::<Urt>::take().store(|r| r.write_vectkey(0x05FA).set_sysresetreq());
+        #[allow(clippy::empty_loop)]
+        loop {}
+    }
+}
+
+#[no_mangle]
+extern "C" fn drone_data_mem_init(load: *const usize, base: *mut usize, end: *const usize) {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!(
+            "   b 1f",
+            "0: ldm {load}!, {{{tmp}}}",
+            "   stm {base}!, {{{tmp}}}",
+            "1: cmp {base}, {end}",
+            "   blo 0b",
+            load = inout(reg) load => _,
+            base = inout(reg) base => _,
+            end = in(reg) end,
+            tmp = out(reg) _,
+            options(nostack),
+        );
+    }
+}
+
+#[no_mangle]
+extern "C" fn drone_zeroed_mem_init(base: *mut usize, end: *const usize) {
+    #[cfg(feature = "host")]
+    return unimplemented!();
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!(
+            "   b 1f",
+            "0: stm {base}!, {{{zero}}}",
+            "1: cmp {base}, {end}",
+            "   blo 0b",
+            base = inout(reg) base => _,
+            end = in(reg) end,
+            zero = in(reg) 0,
+            options(nostack),
+        );
+    }
+}
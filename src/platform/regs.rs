@@ -0,0 +1,416 @@
+//! Core special registers.
+//!
+//! With the `host` feature the registers are emulated with variables, which
+//! record the written values.
+
+#![cfg_attr(feature = "host", allow(dead_code))]
+
+#[cfg(not(feature = "host"))]
+use core::arch::asm;
+#[cfg(feature = "host")]
+use core::sync::atomic::{AtomicU32, Ordering};
+
+macro_rules! mrs {
+    ($reg:literal, $host:ident) => {{
+        #[cfg(feature = "host")]
+        let value = $host.load(Ordering::Relaxed);
+        #[cfg(not(feature = "host"))]
+        let value = unsafe {
+            let value: u32;
+            asm!(
+                concat!("mrs {}, ", $reg),
+                out(reg) value,
+                options(nomem, nostack, preserves_flags),
+            );
+            value
+        };
+        value
+    }};
+}
+
+// Writes don't use `nomem` to prevent memory accesses from moving across them.
+macro_rules! msr {
+    ($reg:literal, $host:ident, $value:expr) => {{
+        #[cfg(feature = "host")]
+        $host.store($value, Ordering::Relaxed);
+        #[cfg(not(feature = "host"))]
+        unsafe {
+            asm!(concat!("msr ", $reg, ", {}"), in(reg) $value, options(nostack, preserves_flags));
+        }
+    }};
+}
+
+macro_rules! host_reg {
+    ($($name:ident),*) => {
+        $(
+            #[cfg(feature = "host")]
+            static $name: AtomicU32 = AtomicU32::new(0);
+        )*
+    };
+}
+
+host_reg!(CONTROL, PRIMASK, FAULTMASK, BASEPRI, MSP, PSP, IPSR, APSR, MSPLIM, PSPLIM);
+
+/// CONTROL register value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Control(u32);
+
+/// APSR register value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Apsr(u32);
+
+impl Control {
+    /// Creates a value from the raw bits.
+    #[inline]
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the raw bits.
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if Thread mode is unprivileged (`nPRIV` bit).
+    #[inline]
+    pub const fn npriv(self) -> bool {
+        self.0 & 1 << 0 != 0
+    }
+
+    /// Returns `true` if Thread mode uses the process stack (`SPSEL` bit).
+    #[inline]
+    pub const fn spsel(self) -> bool {
+        self.0 & 1 << 1 != 0
+    }
+
+    /// Returns `true` if the floating-point context is active (`FPCA` bit).
+    #[cfg(feature = "floating-point-unit")]
+    #[inline]
+    pub const fn fpca(self) -> bool {
+        self.0 & 1 << 2 != 0
+    }
+
+    /// Returns a copy with the `nPRIV` bit set to `value`.
+    #[inline]
+    #[must_use]
+    pub const fn with_npriv(self, value: bool) -> Self {
+        self.with_bit(0, value)
+    }
+
+    /// Returns a copy with the `SPSEL` bit set to `value`.
+    #[inline]
+    #[must_use]
+    pub const fn with_spsel(self, value: bool) -> Self {
+        self.with_bit(1, value)
+    }
+
+    /// Returns a copy with the `FPCA` bit set to `value`.
+    #[cfg(feature = "floating-point-unit")]
+    #[inline]
+    #[must_use]
+    pub const fn with_fpca(self, value: bool) -> Self {
+        self.with_bit(2, value)
+    }
+
+    const fn with_bit(self, bit: u32, value: bool) -> Self {
+        if value { Self(self.0 | 1 << bit) } else { Self(self.0 & !(1 << bit)) }
+    }
+}
+
+impl Apsr {
+    /// Returns the raw bits.
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Negative condition flag.
+    #[inline]
+    pub const fn n(self) -> bool {
+        self.0 & 1 << 31 != 0
+    }
+
+    /// Zero condition flag.
+    #[inline]
+    pub const fn z(self) -> bool {
+        self.0 & 1 << 30 != 0
+    }
+
+    /// Carry condition flag.
+    #[inline]
+    pub const fn c(self) -> bool {
+        self.0 & 1 << 29 != 0
+    }
+
+    /// Overflow condition flag.
+    #[inline]
+    pub const fn v(self) -> bool {
+        self.0 & 1 << 28 != 0
+    }
+
+    /// Saturation flag. Always `false` on ARMv6-M.
+    #[inline]
+    pub const fn q(self) -> bool {
+        self.0 & 1 << 27 != 0
+    }
+}
+
+/// Reads the CONTROL register.
+#[inline]
+pub fn control() -> Control {
+    Control(mrs!("CONTROL", CONTROL))
+}
+
+/// Writes the CONTROL register, followed by an instruction barrier.
+///
+/// # Safety
+///
+/// Changing the privilege level or the stack pointer affects the code
+/// currently running in Thread mode.
+#[inline]
+pub unsafe fn set_control(control: Control) {
+    msr!("CONTROL", CONTROL, control.0);
+    isb();
+}
+
+/// Returns `true` if `PRIMASK` masks all configurable exceptions.
+#[inline]
+pub fn primask() -> bool {
+    mrs!("PRIMASK", PRIMASK) & 1 != 0
+}
+
+/// Writes the `PRIMASK` register.
+///
+/// # Safety
+///
+/// Clearing the mask breaks enclosing critical sections.
+#[inline]
+pub unsafe fn set_primask(primask: bool) {
+    msr!("PRIMASK", PRIMASK, u32::from(primask));
+}
+
+/// Returns `true` if `FAULTMASK` masks all exceptions except NMI.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+#[inline]
+pub fn faultmask() -> bool {
+    mrs!("FAULTMASK", FAULTMASK) & 1 != 0
+}
+
+/// Writes the `FAULTMASK` register.
+///
+/// # Safety
+///
+/// Clearing the mask breaks enclosing critical sections.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+#[inline]
+pub unsafe fn set_faultmask(faultmask: bool) {
+    msr!("FAULTMASK", FAULTMASK, u32::from(faultmask));
+}
+
+/// Reads the raw priority from the `BASEPRI` register. Zero means no masking.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+#[inline]
+pub fn basepri() -> u8 {
+    mrs!("BASEPRI", BASEPRI) as u8
+}
+
+/// Writes the raw priority to the `BASEPRI` register.
+///
+/// # Safety
+///
+/// Lowering the priority breaks enclosing critical sections.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+#[inline]
+pub unsafe fn set_basepri(basepri: u8) {
+    msr!("BASEPRI", BASEPRI, u32::from(basepri));
+}
+
+/// Writes the raw priority to the `BASEPRI` register only if it raises the
+/// masking level.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+#[inline]
+pub fn set_basepri_max(basepri: u8) {
+    #[cfg(feature = "host")]
+    {
+        let current = BASEPRI.load(Ordering::Relaxed);
+        if basepri != 0 && (current == 0 || u32::from(basepri) < current) {
+            BASEPRI.store(u32::from(basepri), Ordering::Relaxed);
+        }
+    }
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!(
+            "msr BASEPRI_MAX, {}",
+            in(reg) u32::from(basepri),
+            options(nostack, preserves_flags),
+        );
+    }
+}
+
+/// Reads the Main Stack Pointer.
+#[inline]
+pub fn msp() -> u32 {
+    mrs!("MSP", MSP)
+}
+
+/// Writes the Main Stack Pointer.
+///
+/// # Safety
+///
+/// The stack pointer must point to a valid stack, and must not be in use by
+/// the current code.
+#[inline]
+pub unsafe fn set_msp(msp: u32) {
+    msr!("MSP", MSP, msp);
+}
+
+/// Reads the Process Stack Pointer.
+#[inline]
+pub fn psp() -> u32 {
+    mrs!("PSP", PSP)
+}
+
+/// Writes the Process Stack Pointer.
+///
+/// # Safety
+///
+/// The stack pointer must point to a valid stack, and must not be in use by
+/// the current code.
+#[inline]
+pub unsafe fn set_psp(psp: u32) {
+    msr!("PSP", PSP, psp);
+}
+
+/// Reads the active exception number from the `IPSR` register. Returns `0` in
+/// Thread mode.
+#[inline]
+pub fn ipsr() -> u16 {
+    (mrs!("IPSR", IPSR) & 0x1FF) as u16
+}
+
+/// Reads the `APSR` register.
+#[inline]
+pub fn apsr() -> Apsr {
+    Apsr(mrs!("APSR", APSR))
+}
+
+/// Reads the Main Stack Pointer Limit.
+#[cfg(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+))]
+#[inline]
+pub fn msplim() -> u32 {
+    mrs!("MSPLIM", MSPLIM)
+}
+
+/// Writes the Main Stack Pointer Limit.
+///
+/// # Safety
+///
+/// The current main stack pointer must not be below the limit.
+#[cfg(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+))]
+#[inline]
+pub unsafe fn set_msplim(msplim: u32) {
+    msr!("MSPLIM", MSPLIM, msplim);
+}
+
+/// Reads the Process Stack Pointer Limit.
+#[cfg(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+))]
+#[inline]
+pub fn psplim() -> u32 {
+    mrs!("PSPLIM", PSPLIM)
+}
+
+/// Writes the Process Stack Pointer Limit.
+///
+/// # Safety
+///
+/// The current process stack pointer must not be below the limit.
+#[cfg(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+))]
+#[inline]
+pub unsafe fn set_psplim(psplim: u32) {
+    msr!("PSPLIM", PSPLIM, psplim);
+}
+
+/// Sets the `IPSR` value returned by [`ipsr`].
+#[cfg(feature = "host")]
+#[inline]
+pub fn set_ipsr(ipsr: u16) {
+    IPSR.store(u32::from(ipsr), Ordering::Relaxed);
+}
+
+/// Sets the `APSR` value returned by [`apsr`].
+#[cfg(feature = "host")]
+#[inline]
+pub fn set_apsr(apsr: u32) {
+    APSR.store(apsr, Ordering::Relaxed);
+}
+
+/// Data Memory Barrier.
+///
+/// Ensures that all explicit memory accesses before the barrier are observed
+/// before any explicit memory accesses after it.
+#[inline]
+pub fn dmb() {
+    #[cfg(feature = "host")]
+    core::sync::atomic::fence(Ordering::SeqCst);
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!("dmb", options(nostack, preserves_flags));
+    }
+}
+
+/// Data Synchronization Barrier.
+///
+/// Completes all explicit memory accesses before the barrier before any
+/// instruction after it executes.
+#[inline]
+pub fn dsb() {
+    #[cfg(feature = "host")]
+    core::sync::atomic::fence(Ordering::SeqCst);
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!("dsb", options(nostack, preserves_flags));
+    }
+}
+
+/// Instruction Synchronization Barrier.
+///
+/// Flushes the pipeline, so that instructions after the barrier are fetched
+/// after it completes, e.g. to observe a CONTROL register change.
+#[inline]
+pub fn isb() {
+    #[cfg(feature = "host")]
+    core::sync::atomic::fence(Ordering::SeqCst);
+    #[cfg(not(feature = "host"))]
+    unsafe {
+        asm!("isb", options(nostack, preserves_flags));
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+use crate::platform::{dwt_cycles, without_interrupts};
+use core::sync::atomic::{AtomicU32, Ordering};
+use drone_core::thr::ThrToken;
+
+// Inclusive cycles of completed activations, maintained so that an activation
+// sees only the activations directly nested into it.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+static CONSUMED: AtomicU32 = AtomicU32::new(0);
+
+/// A token of a thread with an execution budget declared in
+/// [`nvic!`](crate::thr::nvic).
+pub trait ThrBudget: ThrToken {
+    /// The maximum number of cycles of one activation.
+    const BUDGET: u32;
+
+    /// Returns the execution statistics of the thread.
+    fn meter() -> &'static BudgetMeter;
+}
+
+/// Execution statistics of a thread with a budget.
+///
+/// Cycles are measured with `DWT_CYCCNT`, excluding cycles spent in
+/// preempting threads, except naked ones.
+pub struct BudgetMeter {
+    overruns: AtomicU32,
+    max_cycles: AtomicU32,
+}
+
+#[doc(hidden)]
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+pub struct BudgetSpan {
+    start: u32,
+    consumed: u32,
+}
+
+// On ARMv6-M, `thr::nvic!` rejects budgets with a compile-time assertion, which
+// still needs the generated code to resolve.
+#[doc(hidden)]
+#[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+pub struct BudgetSpan;
+
+impl BudgetMeter {
+    /// Creates a new meter.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { overruns: AtomicU32::new(0), max_cycles: AtomicU32::new(0) }
+    }
+
+    /// Returns the number of activations which exceeded the budget.
+    #[inline]
+    pub fn overruns(&self) -> u32 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+
+    /// Returns the maximum number of cycles of one activation.
+    #[inline]
+    pub fn max_cycles(&self) -> u32 {
+        self.max_cycles.load(Ordering::Relaxed)
+    }
+
+    /// Records an activation which took `cycles`. Returns `true` if `cycles`
+    /// exceeds `budget`.
+    ///
+    /// # Safety
+    ///
+    /// Must be called only from the handler of the thread owning the meter.
+    #[inline]
+    pub unsafe fn record(&self, cycles: u32, budget: u32) -> bool {
+        // The handler is the only writer, and it doesn't preempt itself.
+        if cycles > self.max_cycles.load(Ordering::Relaxed) {
+            self.max_cycles.store(cycles, Ordering::Relaxed);
+        }
+        let overrun = cycles > budget;
+        if overrun {
+            let overruns = self.overruns.load(Ordering::Relaxed);
+            self.overruns.store(overruns.saturating_add(1), Ordering::Relaxed);
+        }
+        overrun
+    }
+}
+
+impl Default for BudgetMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+impl BudgetSpan {
+    #[inline]
+    pub fn enter() -> Self {
+        without_interrupts(|| Self {
+            start: dwt_cycles(),
+            consumed: CONSUMED.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Returns the number of cycles since `enter` without nested activations.
+    #[inline]
+    pub fn exit(self) -> u32 {
+        without_interrupts(|| {
+            let elapsed = dwt_cycles().wrapping_sub(self.start);
+            let nested = CONSUMED.load(Ordering::Relaxed).wrapping_sub(self.consumed);
+            CONSUMED.store(self.consumed.wrapping_add(elapsed), Ordering::Relaxed);
+            elapsed.saturating_sub(nested)
+        })
+    }
+}
+
+#[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+impl BudgetSpan {
+    #[inline]
+    pub fn enter() -> Self {
+        unreachable!()
+    }
+
+    #[inline]
+    pub fn exit(self) -> u32 {
+        unreachable!()
+    }
+}
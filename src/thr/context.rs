@@ -0,0 +1,168 @@
+//! Execution context introspection.
+//!
+//! The active exception number is read from `IPSR`, which holds the same value
+//! as `ICSR.VECTACTIVE` without a memory access.
+
+use crate::platform::regs;
+use crate::thr::nvic::{NVIC_BLOCK_COUNT, NVIC_IABR, NVIC_IPR, NVIC_ISPR};
+use crate::thr::priority::read_priority;
+use crate::thr::Priority;
+use core::ptr::read_volatile;
+
+const SCB_SHPR1: usize = 0xE000_ED18;
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+const SCB_AIRCR: usize = 0xE000_ED0C;
+
+/// Mapping from exception numbers to thread indices.
+///
+/// Implemented by [`thr::nvic!`](crate::thr::nvic) for the thread type.
+pub trait VectorMap {
+    /// Returns the index of the thread handling the exception `exc_num`, or
+    /// `None` if the exception has no thread or a naked handler.
+    fn thr_idx(exc_num: u16) -> Option<u16>;
+}
+
+/// Current execution priority of the processor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExecPriority {
+    /// Thread mode without priority boosting.
+    Base,
+    /// Priority of the active exception or `BASEPRI`, whichever is higher.
+    /// Sub-priority bits are cleared.
+    Level(Priority),
+    /// `PRIMASK` is set, priority 0.
+    Primask,
+    /// HardFault is active or `FAULTMASK` is set, priority -1.
+    HardFault,
+    /// NMI is active, priority -2.
+    Nmi,
+}
+
+/// Snapshot of pending and active states of all interrupts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntSnapshot {
+    pending: [u32; NVIC_BLOCK_COUNT],
+    active: [u32; NVIC_BLOCK_COUNT],
+}
+
+/// Returns the number of the active exception from `IPSR`, or `0` in thread
+/// mode.
+#[inline]
+pub fn vect_active() -> u16 {
+    regs::ipsr()
+}
+
+/// Returns `true` if the processor is in handler mode.
+#[inline]
+pub fn in_interrupt() -> bool {
+    vect_active() != 0
+}
+
+/// Returns the index of the current thread from `thr::nvic!`, or `None` in
+/// thread mode or in a naked handler.
+#[inline]
+pub fn current<T: VectorMap>() -> Option<u16> {
+    match vect_active() {
+        0 => None,
+        exc_num => T::thr_idx(exc_num),
+    }
+}
+
+/// Returns the current execution priority, taking into account the active
+/// exception, `BASEPRI`, `PRIMASK`, and `FAULTMASK`.
+pub fn execution_priority() -> ExecPriority {
+    let exc_num = vect_active();
+    match exc_num {
+        2 => return ExecPriority::Nmi,
+        3 => return ExecPriority::HardFault,
+        _ => {}
+    }
+    let (primask, faultmask, basepri) = masks();
+    if faultmask {
+        return ExecPriority::HardFault;
+    }
+    if primask {
+        return ExecPriority::Primask;
+    }
+    let active = match exc_num {
+        0 | 1 => None,
+        4..=15 => Some(unsafe { read_priority(SCB_SHPR1 + usize::from(exc_num) - 4) }),
+        _ => Some(unsafe { read_priority(NVIC_IPR + usize::from(exc_num) - 16) }),
+    };
+    let active = active.map(group_priority);
+    // BASEPRI of zero has no effect.
+    let basepri = (basepri != 0).then(|| group_priority(Priority::from_raw(basepri)));
+    match (active, basepri) {
+        (Some(a), Some(b)) => ExecPriority::Level(a.min(b)),
+        (Some(level), None) | (None, Some(level)) => ExecPriority::Level(level),
+        (None, None) => ExecPriority::Base,
+    }
+}
+
+impl IntSnapshot {
+    /// Reads pending and active states of interrupts in every NVIC block.
+    pub fn take() -> Self {
+        let mut snapshot = Self { pending: [0; NVIC_BLOCK_COUNT], active: [0; NVIC_BLOCK_COUNT] };
+        for block in 0..NVIC_BLOCK_COUNT {
+            unsafe {
+                snapshot.pending[block] = read_volatile((NVIC_ISPR as *const u32).add(block));
+                snapshot.active[block] = read_volatile((NVIC_IABR as *const u32).add(block));
+            }
+        }
+        snapshot
+    }
+
+    /// Returns `true` if the interrupt `int_num` was pending.
+    pub fn is_pending(&self, int_num: u16) -> bool {
+        test_bit(&self.pending, int_num)
+    }
+
+    /// Returns `true` if the interrupt `int_num` was active.
+    pub fn is_active(&self, int_num: u16) -> bool {
+        test_bit(&self.active, int_num)
+    }
+
+    /// Returns an iterator over the numbers of pending interrupts.
+    pub fn pending(&self) -> impl Iterator<Item = u16> + '_ {
+        iter_bits(&self.pending)
+    }
+
+    /// Returns an iterator over the numbers of active interrupts.
+    pub fn active(&self) -> impl Iterator<Item = u16> + '_ {
+        iter_bits(&self.active)
+    }
+}
+
+fn test_bit(blocks: &[u32], int_num: u16) -> bool {
+    blocks
+        .get(usize::from(int_num >> 5))
+        .is_some_and(|block| block & 1 << (int_num & 0b1_1111) != 0)
+}
+
+fn iter_bits(blocks: &[u32]) -> impl Iterator<Item = u16> + '_ {
+    (0..blocks.len() as u16 * 32).filter(|&int_num| test_bit(blocks, int_num))
+}
+
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+fn group_priority(priority: Priority) -> Priority {
+    let prigroup = (unsafe { read_volatile(SCB_AIRCR as *const u32) } >> 8) & 0b111;
+    Priority::from_raw(priority.to_raw() & !((1 << (prigroup + 1)) - 1) as u8)
+}
+
+// ARMv6-M has no sub-priorities.
+#[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+fn group_priority(priority: Priority) -> Priority {
+    priority
+}
+
+/// Returns `PRIMASK`, `FAULTMASK`, and `BASEPRI`.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+fn masks() -> (bool, bool, u8) {
+    (regs::primask(), regs::faultmask(), regs::basepri())
+}
+
+/// Returns `PRIMASK`, `FAULTMASK`, and `BASEPRI`.
+#[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+fn masks() -> (bool, bool, u8) {
+    (regs::primask(), false, 0)
+}
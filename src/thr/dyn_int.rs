@@ -0,0 +1,140 @@
+use crate::thr::nvic::{NVIC_IABR, NVIC_ICER, NVIC_ICPR, NVIC_IPR, NVIC_ISER, NVIC_ISPR};
+use crate::thr::priority::{read_priority, write_priority};
+use crate::thr::wake::WakeInt;
+use crate::thr::{IntToken, NvicBlock, Priority};
+use core::ptr::{read_volatile, write_volatile};
+use core::task::Waker;
+
+/// Type-erased interrupt handle.
+///
+/// Carries the interrupt number and the NVIC block at run time instead of the
+/// type of an [`IntToken`], so that drivers and trait objects can hold
+/// interrupts without generic parameters.
+///
+/// ```no_run
+/// # use drone_cortexm::thr::prelude::*;
+/// use drone_cortexm::thr::DynInt;
+///
+/// struct Driver {
+///     int: DynInt,
+/// }
+///
+/// impl Driver {
+///     fn new(int: impl IntToken) -> Self {
+///         let int = DynInt::new(int);
+///         int.enable();
+///         Self { int }
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DynInt {
+    int_num: u16,
+    block: usize,
+}
+
+impl DynInt {
+    /// Creates a new handle from the interrupt token.
+    #[inline]
+    pub fn new<T: IntToken>(_token: T) -> Self {
+        Self { int_num: T::INT_NUM, block: T::NvicBlock::BLOCK_NUM }
+    }
+
+    /// Returns the number of the interrupt.
+    #[inline]
+    pub fn int_num(self) -> u16 {
+        self.int_num
+    }
+
+    /// Returns the number of the NVIC block the interrupt belongs to.
+    #[inline]
+    pub fn block_num(self) -> usize {
+        self.block
+    }
+
+    /// Enables the interrupt.
+    #[inline]
+    pub fn enable(self) {
+        self.write(NVIC_ISER);
+    }
+
+    /// Disables the interrupt.
+    #[inline]
+    pub fn disable(self) {
+        self.write(NVIC_ICER);
+    }
+
+    /// Returns `true` if the interrupt is enabled.
+    #[inline]
+    pub fn is_enabled(self) -> bool {
+        self.read(NVIC_ISER)
+    }
+
+    /// Sets the interrupt pending.
+    #[inline]
+    pub fn set_pending(self) {
+        self.write(NVIC_ISPR);
+    }
+
+    /// Clears the interrupt pending state.
+    #[inline]
+    pub fn clear_pending(self) {
+        self.write(NVIC_ICPR);
+    }
+
+    /// Returns `true` if the interrupt is pending.
+    #[inline]
+    pub fn is_pending(self) -> bool {
+        self.read(NVIC_ISPR)
+    }
+
+    /// Returns `true` if the interrupt is active.
+    #[inline]
+    pub fn is_active(self) -> bool {
+        self.read(NVIC_IABR)
+    }
+
+    /// Reads the priority of the interrupt.
+    #[inline]
+    pub fn priority(self) -> Priority {
+        unsafe { read_priority(NVIC_IPR + usize::from(self.int_num)) }
+    }
+
+    /// Writes the priority of the interrupt.
+    #[inline]
+    pub fn set_priority(self, priority: Priority) {
+        unsafe { write_priority(NVIC_IPR + usize::from(self.int_num), priority) };
+    }
+
+    /// Wakes up the thread.
+    #[inline]
+    pub fn wakeup(self) {
+        WakeInt::new(self.int_num).wakeup();
+    }
+
+    /// Returns a handle for waking up the thread.
+    #[inline]
+    pub fn waker(self) -> Waker {
+        WakeInt::new(self.int_num).to_waker()
+    }
+
+    fn read(self, base: usize) -> bool {
+        let value = unsafe { read_volatile((base as *const u32).add(self.block)) };
+        value & self.mask() != 0
+    }
+
+    fn write(self, base: usize) {
+        unsafe { write_volatile((base as *mut u32).add(self.block), self.mask()) };
+    }
+
+    fn mask(self) -> u32 {
+        1 << (self.int_num & 0b1_1111)
+    }
+}
+
+impl<T: IntToken> From<T> for DynInt {
+    #[inline]
+    fn from(token: T) -> Self {
+        Self::new(token)
+    }
+}
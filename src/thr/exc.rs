@@ -0,0 +1,351 @@
+#[cfg(feature = "security-extension")]
+use crate::map::thr::exc::SecureFault;
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+use crate::map::thr::exc::{BusFault, Debug, MemManage, UsageFault};
+use crate::map::thr::exc::{Nmi, PendSv, SvCall, SysTick};
+use crate::platform::without_interrupts;
+use crate::thr::priority::{read_priority, write_priority};
+use crate::thr::wake::{WakeNmi, WakePendSv, WakeSysTick};
+use crate::thr::Priority;
+use core::ptr::{read_volatile, write_volatile};
+use core::task::Waker;
+use drone_core::thr::ThrToken;
+
+const SCB_ICSR: usize = 0xE000_ED04;
+const SCB_SHPR1: usize = 0xE000_ED18;
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+const SCB_SHCSR: usize = 0xE000_ED24;
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+const SCB_DEMCR: usize = 0xE000_EDFC;
+
+/// A system exception.
+pub trait Exception {
+    /// The number of the exception.
+    const EXC_NUM: u8;
+}
+
+/// An exception token.
+pub trait ExcToken: ThrToken {
+    /// The exception the thread is bound to.
+    type Exc: Exception;
+
+    /// Wakes up the thread.
+    ///
+    /// # Safety
+    ///
+    /// This function doesn't check for the exception token ownership.
+    #[inline]
+    unsafe fn wakeup_unchecked()
+    where
+        Self::Exc: ExcWakeup,
+    {
+        unsafe { <Self::Exc as ExcWakeup>::wakeup() };
+    }
+
+    /// Returns a handle for waking up a thread.
+    ///
+    /// # Safety
+    ///
+    /// This function doesn't check for the exception token ownership.
+    #[inline]
+    unsafe fn waker_unchecked() -> Waker
+    where
+        Self::Exc: ExcWakeup,
+    {
+        unsafe { <Self::Exc as ExcWakeup>::waker() }
+    }
+}
+
+/// An exception with configurable priority in `SHPRn`.
+pub trait ExcPriority: Exception {}
+
+/// An exception that can be enabled and disabled.
+pub trait ExcEnable: Exception {
+    #[doc(hidden)]
+    const ENABLE: RegBit;
+}
+
+/// An exception with software-accessible pending state.
+pub trait ExcPending: Exception {
+    #[doc(hidden)]
+    const PENDING: PendingBits;
+}
+
+/// An exception which can be set pending by software to wake up its thread.
+pub trait ExcWakeup: Exception {
+    #[doc(hidden)]
+    unsafe fn wakeup();
+
+    #[doc(hidden)]
+    unsafe fn waker() -> Waker;
+}
+
+/// An exception with readable active state in `SHCSR`.
+pub trait ExcActive: Exception {
+    #[doc(hidden)]
+    const ACTIVE: RegBit;
+}
+
+#[doc(hidden)]
+#[derive(Clone, Copy)]
+pub struct RegBit {
+    addr: usize,
+    bit: u32,
+}
+
+#[doc(hidden)]
+pub enum PendingBits {
+    /// Separate write-one-to-set and write-one-to-clear bits in `ICSR`.
+    Icsr { set: u32, clear: u32 },
+    /// Read-write bit.
+    ReadWrite(RegBit),
+}
+
+/// Priority, enable, pending, and active state control for exception
+/// threads.
+///
+/// Each method is available only if the exception implements the
+/// corresponding capability trait on the current core.
+pub trait ThrExc: ExcToken {
+    /// Reads the priority of the exception.
+    #[inline]
+    fn priority(self) -> Priority
+    where
+        Self::Exc: ExcPriority,
+    {
+        unsafe { read_priority(shpr_addr::<Self::Exc>()) }
+    }
+
+    /// Writes the priority of the exception.
+    #[inline]
+    fn set_priority(self, priority: Priority)
+    where
+        Self::Exc: ExcPriority,
+    {
+        unsafe { write_priority(shpr_addr::<Self::Exc>(), priority) };
+    }
+
+    /// Enables the exception.
+    #[inline]
+    fn enable(self)
+    where
+        Self::Exc: ExcEnable,
+    {
+        unsafe { <Self::Exc as ExcEnable>::ENABLE.modify(true) };
+    }
+
+    /// Disables the exception.
+    #[inline]
+    fn disable(self)
+    where
+        Self::Exc: ExcEnable,
+    {
+        unsafe { <Self::Exc as ExcEnable>::ENABLE.modify(false) };
+    }
+
+    /// Returns `true` if the exception is enabled.
+    #[inline]
+    fn is_enabled(self) -> bool
+    where
+        Self::Exc: ExcEnable,
+    {
+        unsafe { <Self::Exc as ExcEnable>::ENABLE.read() }
+    }
+
+    /// Sets the exception pending.
+    #[inline]
+    fn set_pending(self)
+    where
+        Self::Exc: ExcPending,
+    {
+        unsafe { <Self::Exc as ExcPending>::PENDING.set(true) };
+    }
+
+    /// Clears the exception pending state.
+    #[inline]
+    fn clear_pending(self)
+    where
+        Self::Exc: ExcPending,
+    {
+        unsafe { <Self::Exc as ExcPending>::PENDING.set(false) };
+    }
+
+    /// Returns `true` if the exception is pending.
+    #[inline]
+    fn is_pending(self) -> bool
+    where
+        Self::Exc: ExcPending,
+    {
+        unsafe { <Self::Exc as ExcPending>::PENDING.read() }
+    }
+
+    /// Returns `true` if the exception is active.
+    #[inline]
+    fn is_active(self) -> bool
+    where
+        Self::Exc: ExcActive,
+    {
+        unsafe { <Self::Exc as ExcActive>::ACTIVE.read() }
+    }
+}
+
+impl<T: ExcToken> ThrExc for T {}
+
+impl RegBit {
+    const fn new(addr: usize, bit: u32) -> Self {
+        Self { addr, bit }
+    }
+
+    unsafe fn read(&self) -> bool {
+        unsafe { read_volatile(self.addr as *const u32) & 1 << self.bit != 0 }
+    }
+
+    pub(crate) unsafe fn modify(&self, value: bool) {
+        without_interrupts(|| unsafe {
+            let reg = self.addr as *mut u32;
+            let mask = 1 << self.bit;
+            let old = read_volatile(reg);
+            write_volatile(reg, if value { old | mask } else { old & !mask });
+        });
+    }
+}
+
+impl PendingBits {
+    unsafe fn read(&self) -> bool {
+        match self {
+            Self::Icsr { set, .. } => unsafe { RegBit::new(SCB_ICSR, *set).read() },
+            Self::ReadWrite(bit) => unsafe { bit.read() },
+        }
+    }
+
+    unsafe fn set(&self, value: bool) {
+        match self {
+            Self::Icsr { set, clear } => unsafe {
+                write_volatile(SCB_ICSR as *mut u32, 1 << if value { *set } else { *clear });
+            },
+            Self::ReadWrite(bit) => unsafe { bit.modify(value) },
+        }
+    }
+}
+
+pub(crate) const fn shpr_addr<T: ExcPriority>() -> usize {
+    SCB_SHPR1 + T::EXC_NUM as usize - 4
+}
+
+macro_rules! exc_priority {
+    ($($(#[$attr:meta])* $exc:ident,)*) => {
+        $($(#[$attr])* impl ExcPriority for $exc {})*
+    };
+}
+
+macro_rules! exc_enable {
+    ($($(#[$attr:meta])* $exc:ident => $addr:ident[$bit:expr],)*) => {
+        $(
+            $(#[$attr])*
+            impl ExcEnable for $exc {
+                const ENABLE: RegBit = RegBit::new($addr, $bit);
+            }
+        )*
+    };
+}
+
+macro_rules! exc_pending {
+    ($($(#[$attr:meta])* $exc:ident => $pending:expr,)*) => {
+        $(
+            $(#[$attr])*
+            impl ExcPending for $exc {
+                const PENDING: PendingBits = $pending;
+            }
+        )*
+    };
+}
+
+macro_rules! exc_active {
+    ($($(#[$attr:meta])* $exc:ident => $addr:ident[$bit:expr],)*) => {
+        $(
+            $(#[$attr])*
+            impl ExcActive for $exc {
+                const ACTIVE: RegBit = RegBit::new($addr, $bit);
+            }
+        )*
+    };
+}
+
+macro_rules! exc_wakeup {
+    ($($exc:ident => $wake:ident,)*) => {
+        $(
+            impl ExcWakeup for $exc {
+                #[inline]
+                unsafe fn wakeup() {
+                    $wake::wakeup();
+                }
+
+                #[inline]
+                unsafe fn waker() -> Waker {
+                    $wake::to_waker()
+                }
+            }
+        )*
+    };
+}
+
+exc_wakeup! {
+    Nmi => WakeNmi,
+    PendSv => WakePendSv,
+    SysTick => WakeSysTick,
+}
+
+exc_priority! {
+    SvCall,
+    PendSv,
+    SysTick,
+}
+
+exc_pending! {
+    PendSv => PendingBits::Icsr { set: 28, clear: 27 },
+    SysTick => PendingBits::Icsr { set: 26, clear: 25 },
+}
+
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+exc_priority! {
+    MemManage,
+    BusFault,
+    UsageFault,
+    #[cfg(feature = "security-extension")]
+    SecureFault,
+    Debug,
+}
+
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+exc_enable! {
+    MemManage => SCB_SHCSR[16],
+    BusFault => SCB_SHCSR[17],
+    UsageFault => SCB_SHCSR[18],
+    #[cfg(feature = "security-extension")]
+    SecureFault => SCB_SHCSR[19],
+    Debug => SCB_DEMCR[16],
+}
+
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+exc_pending! {
+    MemManage => PendingBits::ReadWrite(RegBit::new(SCB_SHCSR, 13)),
+    BusFault => PendingBits::ReadWrite(RegBit::new(SCB_SHCSR, 14)),
+    UsageFault => PendingBits::ReadWrite(RegBit::new(SCB_SHCSR, 12)),
+    #[cfg(feature = "security-extension")]
+    SecureFault => PendingBits::ReadWrite(RegBit::new(SCB_SHCSR, 20)),
+    SvCall => PendingBits::ReadWrite(RegBit::new(SCB_SHCSR, 15)),
+    Debug => PendingBits::ReadWrite(RegBit::new(SCB_DEMCR, 17)),
+}
+
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+exc_active! {
+    MemManage => SCB_SHCSR[0],
+    BusFault => SCB_SHCSR[1],
+    UsageFault => SCB_SHCSR[3],
+    #[cfg(feature = "security-extension")]
+    SecureFault => SCB_SHCSR[4],
+    SvCall => SCB_SHCSR[7],
+    Debug => SCB_SHCSR[8],
+    PendSv => SCB_SHCSR[10],
+    SysTick => SCB_SHCSR[11],
+}
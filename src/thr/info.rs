@@ -0,0 +1,184 @@
+//! Machine-readable vector table description.
+//!
+//! [`thr::nvic!`](crate::thr::nvic) emits the description with the `info`
+//! key:
+//!
+//! ```text
+//! /// Vector table description.
+//! info => pub VECTORS_INFO;
+//! ```
+//!
+//! The description is a byte array placed into the `.drone_vectors` ELF
+//! section, so that host-side tools can extract it from the firmware image and
+//! decode it with [`parse`]. The section is not used at run time, so the
+//! linker script must keep it out of the flash image by marking it
+//! non-allocatable:
+//!
+//! ```text
+//! .drone_vectors 0 (INFO) : { KEEP(*(.drone_vectors)) }
+//! ```
+//!
+//! With the `host` feature, the description is an ordinary static without a
+//! section. The encoding is little-endian:
+//!
+//! | Size     | Field                                            |
+//! |----------|--------------------------------------------------|
+//! | 4        | Magic `b"DRVT"`                                  |
+//! | 1        | Format version, currently [`VERSION`]            |
+//! | 2        | Number of entries                                |
+//!
+//! followed by the entries:
+//!
+//! | Size     | Field                                            |
+//! |----------|--------------------------------------------------|
+//! | 2        | Vector number (exception number)                 |
+//! | 1        | Kind: `0` - inner, `1` - outer, `2` - naked      |
+//! | 1        | Flags: bit 0 - priority is present               |
+//! | 1        | Priority level                                   |
+//! | 1        | Name length `N`                                  |
+//! | `N`      | Name in UTF-8                                    |
+
+use core::fmt;
+use core::str;
+
+/// Magic bytes at the beginning of the description.
+pub const MAGIC: [u8; 4] = *b"DRVT";
+
+/// The current version of the format.
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: usize = 7;
+const ENTRY_HEADER_LEN: usize = 6;
+
+/// Description of a vector declared in [`thr::nvic!`](crate::thr::nvic).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorInfo<'a> {
+    /// The vector number, which is the exception number.
+    pub number: u16,
+    /// The thread name.
+    pub name: &'a str,
+    /// The thread kind.
+    pub kind: VectorKind,
+    /// The declared priority level.
+    pub priority: Option<u8>,
+}
+
+/// Thread kind of a vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorKind {
+    /// A regular thread.
+    Inner,
+    /// A thread with a custom handler.
+    Outer,
+    /// A handler inserted directly to the vector table.
+    Naked,
+}
+
+/// Error returned from [`parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoError {
+    /// The data doesn't start with [`MAGIC`].
+    BadMagic,
+    /// The format version is not supported.
+    UnsupportedVersion(u8),
+    /// The data ends in the middle of an entry.
+    Truncated,
+    /// An entry has an unknown kind.
+    BadKind(u8),
+    /// An entry name is not valid UTF-8.
+    BadName,
+}
+
+/// Iterator over vector descriptions returned from [`parse`].
+#[derive(Clone)]
+pub struct VectorInfoIter<'a> {
+    data: &'a [u8],
+    remaining: u16,
+}
+
+/// Validates the description `data` and returns an iterator over its entries.
+///
+/// # Errors
+///
+/// If the data is malformed.
+pub fn parse(data: &[u8]) -> Result<VectorInfoIter<'_>, InfoError> {
+    if !data.starts_with(&MAGIC) {
+        return Err(InfoError::BadMagic);
+    }
+    if data.len() < HEADER_LEN {
+        return Err(InfoError::Truncated);
+    }
+    if data[4] != VERSION {
+        return Err(InfoError::UnsupportedVersion(data[4]));
+    }
+    let iter = VectorInfoIter {
+        data: &data[HEADER_LEN..],
+        remaining: u16::from_le_bytes([data[5], data[6]]),
+    };
+    let mut validate = iter.clone();
+    while validate.remaining > 0 {
+        validate.decode()?;
+    }
+    Ok(iter)
+}
+
+impl<'a> VectorInfoIter<'a> {
+    fn decode(&mut self) -> Result<VectorInfo<'a>, InfoError> {
+        if self.data.len() < ENTRY_HEADER_LEN {
+            return Err(InfoError::Truncated);
+        }
+        let (header, rest) = self.data.split_at(ENTRY_HEADER_LEN);
+        let name_len = usize::from(header[5]);
+        if rest.len() < name_len {
+            return Err(InfoError::Truncated);
+        }
+        let (name, rest) = rest.split_at(name_len);
+        let kind = match header[2] {
+            0 => VectorKind::Inner,
+            1 => VectorKind::Outer,
+            2 => VectorKind::Naked,
+            kind => return Err(InfoError::BadKind(kind)),
+        };
+        let info = VectorInfo {
+            number: u16::from_le_bytes([header[0], header[1]]),
+            name: str::from_utf8(name).map_err(|_| InfoError::BadName)?,
+            kind,
+            priority: (header[3] & 1 != 0).then_some(header[4]),
+        };
+        self.data = rest;
+        self.remaining -= 1;
+        Ok(info)
+    }
+}
+
+impl<'a> Iterator for VectorInfoIter<'a> {
+    type Item = VectorInfo<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        // The data is validated in `parse`.
+        self.decode().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::from(self.remaining), Some(usize::from(self.remaining)))
+    }
+}
+
+impl ExactSizeIterator for VectorInfoIter<'_> {}
+
+impl fmt::Display for InfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadMagic => write!(f, "Vector table description has bad magic."),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Vector table description version {version} is not supported.")
+            }
+            Self::Truncated => write!(f, "Vector table description is truncated."),
+            Self::BadKind(kind) => write!(f, "Vector table description has bad kind {kind}."),
+            Self::BadName => write!(f, "Vector table description has bad name."),
+        }
+    }
+}
@@ -3,6 +3,11 @@
 use crate::map::periph;
 use crate::map::reg::scb;
 use crate::reg::prelude::*;
+use crate::thr::exc::{shpr_addr, RegBit};
+use crate::thr::nvic::{NVIC_IPR, NVIC_ISER};
+use crate::thr::priority::write_priority;
+use crate::thr::{ExcEnable, ExcPriority, Priority};
+use core::ptr::write_volatile;
 use drone_core::token::Token;
 
 /// Threads initialization token.
@@ -15,6 +20,11 @@ pub unsafe trait ThrsInitToken: Token {
     /// The set of thread tokens.
     type ThrTokens: Token;
 
+    /// Priorities and enabled states declared in [`thr::nvic!`](crate::thr::nvic),
+    /// applied by [`init_extended`](ThrsInitToken::init_extended).
+    #[doc(hidden)]
+    const THR_CONFIG: &'static [ThrConfig] = &[];
+
     /// Initializes the thread system and returns a set of thread tokens.
     ///
     /// # Examples
@@ -75,6 +85,9 @@ pub unsafe trait ThrsInitToken: Token {
         }
         drop(stkalign);
         drop(nonbasethrdena);
+        for config in Self::THR_CONFIG {
+            unsafe { config.apply() };
+        }
         (unsafe { Self::ThrTokens::take() }, ThrInitExtended {
             scb_ccr_bfhfnmign,
             scb_ccr_div_0_trp,
@@ -141,6 +154,63 @@ pub struct ThrInitExtended {
     pub scb_ccr_usersetmpend: scb::ccr::Usersetmpend<Srt>,
 }
 
+/// Thread settings declared in [`thr::nvic!`](crate::thr::nvic).
+///
+/// The constructors are `const fn`s, so levels out of the range of
+/// [`PRIORITY_BITS`](crate::thr::PRIORITY_BITS) and unsupported exception
+/// settings are rejected at compile time.
+#[doc(hidden)]
+pub struct ThrConfig {
+    priority: Option<(usize, Priority)>,
+    enable: Option<Enable>,
+}
+
+enum Enable {
+    Int(u16),
+    Exc(RegBit),
+}
+
+impl ThrConfig {
+    /// Creates an empty setting.
+    pub const fn new() -> Self {
+        Self { priority: None, enable: None }
+    }
+
+    /// Sets the priority `level` of the interrupt `int_num`.
+    pub const fn int_priority(self, int_num: u16, level: u8) -> Self {
+        Self { priority: Some((NVIC_IPR + int_num as usize, Priority::from_level(level))), ..self }
+    }
+
+    /// Enables the interrupt `int_num`.
+    pub const fn int_enabled(self, int_num: u16) -> Self {
+        Self { enable: Some(Enable::Int(int_num)), ..self }
+    }
+
+    /// Sets the priority `level` of the exception `T`.
+    pub const fn exc_priority<T: ExcPriority>(self, level: u8) -> Self {
+        Self { priority: Some((shpr_addr::<T>(), Priority::from_level(level))), ..self }
+    }
+
+    /// Enables the exception `T`.
+    pub const fn exc_enabled<T: ExcEnable>(self) -> Self {
+        Self { enable: Some(Enable::Exc(T::ENABLE)), ..self }
+    }
+
+    unsafe fn apply(&self) {
+        if let Some((addr, priority)) = self.priority {
+            unsafe { write_priority(addr, priority) };
+        }
+        match self.enable {
+            Some(Enable::Int(int_num)) => unsafe {
+                let iser = (NVIC_ISER as *mut u32).add(usize::from(int_num >> 5));
+                write_volatile(iser, 1 << (int_num & 0b1_1111));
+            },
+            Some(Enable::Exc(bit)) => unsafe { bit.modify(true) },
+            None => {}
+        }
+    }
+}
+
 #[cfg(feature = "memory-protection-unit")]
 mod mpu {
     use crate::map::periph;
@@ -0,0 +1,143 @@
+use crate::thr::nvic::{NVIC_BLOCK_COUNT, NVIC_IABR, NVIC_ICER, NVIC_ICPR, NVIC_ISER, NVIC_ISPR};
+use crate::thr::{IntToken, NvicBlock};
+use core::ptr::{read_volatile, write_volatile};
+
+/// A set of interrupts spanning multiple NVIC blocks.
+///
+/// The set is built from interrupt tokens with const methods, and each
+/// operation writes only the NVIC registers of the blocks containing the
+/// interrupts of the set.
+///
+/// ```no_run
+/// # use drone_cortexm::thr::prelude::*;
+/// use drone_cortexm::thr::IntSet;
+///
+/// fn enable_all(a: impl IntToken, b: impl IntToken, c: impl IntToken) -> IntSet {
+///     let set = IntSet::new().with(a).with(b).with(c);
+///     set.enable();
+///     set
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IntSet {
+    blocks: [u32; NVIC_BLOCK_COUNT],
+}
+
+impl IntSet {
+    /// Creates an empty set.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { blocks: [0; NVIC_BLOCK_COUNT] }
+    }
+
+    /// Adds the interrupt of the token to the set.
+    #[inline]
+    #[must_use]
+    pub const fn with<T: IntToken>(mut self, _token: T) -> Self {
+        self.blocks[T::NvicBlock::BLOCK_NUM] |= 1 << (T::INT_NUM & 0b1_1111);
+        self
+    }
+
+    /// Returns the union of two sets.
+    #[inline]
+    #[must_use]
+    pub const fn union(mut self, other: Self) -> Self {
+        let mut i = 0;
+        while i < NVIC_BLOCK_COUNT {
+            self.blocks[i] |= other.blocks[i];
+            i += 1;
+        }
+        self
+    }
+
+    /// Returns `true` if the set contains the interrupt `int_num`.
+    #[inline]
+    pub fn contains(&self, int_num: u16) -> bool {
+        self.blocks
+            .get(usize::from(int_num >> 5))
+            .is_some_and(|block| block & 1 << (int_num & 0b1_1111) != 0)
+    }
+
+    /// Returns `true` if the set contains no interrupts.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|&block| block == 0)
+    }
+
+    /// Returns an iterator over the interrupt numbers of the set.
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        (0..NVIC_BLOCK_COUNT as u16 * 32).filter(|&int_num| self.contains(int_num))
+    }
+
+    /// Enables all interrupts of the set.
+    #[inline]
+    pub fn enable(&self) {
+        self.write(NVIC_ISER);
+    }
+
+    /// Disables all interrupts of the set.
+    #[inline]
+    pub fn disable(&self) {
+        self.write(NVIC_ICER);
+    }
+
+    /// Sets all interrupts of the set pending.
+    #[inline]
+    pub fn set_pending(&self) {
+        self.write(NVIC_ISPR);
+    }
+
+    /// Clears the pending state of all interrupts of the set.
+    #[inline]
+    pub fn clear_pending(&self) {
+        self.write(NVIC_ICPR);
+    }
+
+    /// Returns the subset of enabled interrupts.
+    #[inline]
+    #[must_use]
+    pub fn enabled(&self) -> Self {
+        self.read(NVIC_ISER)
+    }
+
+    /// Returns the subset of pending interrupts.
+    #[inline]
+    #[must_use]
+    pub fn pending(&self) -> Self {
+        self.read(NVIC_ISPR)
+    }
+
+    /// Returns the subset of active interrupts.
+    #[inline]
+    #[must_use]
+    pub fn active(&self) -> Self {
+        self.read(NVIC_IABR)
+    }
+
+    fn read(&self, base: usize) -> Self {
+        let mut set = Self::new();
+        for (i, &mask) in self.blocks.iter().enumerate() {
+            if mask != 0 {
+                set.blocks[i] = unsafe { read_volatile((base as *const u32).add(i)) } & mask;
+            }
+        }
+        set
+    }
+
+    fn write(&self, base: usize) {
+        for (i, &mask) in self.blocks.iter().enumerate() {
+            // Writing zeros has no effect, so only the affected words are
+            // written.
+            if mask != 0 {
+                unsafe { write_volatile((base as *mut u32).add(i), mask) };
+            }
+        }
+    }
+}
+
+impl<T: IntToken> From<T> for IntSet {
+    #[inline]
+    fn from(token: T) -> Self {
+        Self::new().with(token)
+    }
+}
@@ -27,12 +27,40 @@
 //!
 //!     // This item can be omitted if the vector table doesn't need to relocate into RAM.
 //!     /// Vector table type.
-//!     #[repr(align(256))] // depends on the VTOR register and can vary for different cores
+//!     // The alignment required by the VTOR register is computed from the number of
+//!     // vectors. An explicit `#[repr(align(N))]` attribute must not be smaller.
 //!     vtable => pub Vtable;
 //!
 //!     /// Threads initialization token.
 //!     init => pub ThrsInit;
 //!
+//!     // This item can be omitted. See the `thr::info` module documentation for details.
+//!     /// Vector table description for host-side tools.
+//!     info => pub VECTORS_INFO;
+//!
+//!     // This item can be omitted. The handler is installed into all unassigned interrupt
+//!     // vectors below the highest declared interrupt number. When a spurious interrupt is
+//!     // taken, the interrupt is disabled, and the handler is called with its number.
+//!     default => unhandled_int;
+//!
+//!     // This item can be omitted. Enables interrupt storm detection for interrupt threads
+//!     // declaring a `rate` option. The window is measured either in DWT cycles (`dwt(N)`,
+//!     // requires `DWT_CTRL.CYCCNTENA`, not available on ARMv6-M), or in SysTick periods
+//!     // counted by the `sys_tick` thread (`sys_tick(N)`). When a thread is activated more
+//!     // than `rate` times within the window, its interrupt is disabled, the activation is
+//!     // dropped, and the hook is called with the interrupt number.
+//!     storm => {
+//!         window => sys_tick(10);
+//!         hook => interrupt_storm;
+//!     };
+//!
+//!     // This item can be omitted. The hook is called with the thread index and the number of
+//!     // cycles when an activation of a thread declaring a `budget` option exceeds its budget.
+//!     // Activations are timed with `DWT_CYCCNT`, which must be enabled, and cycles spent in
+//!     // preempting threads, except naked ones, are excluded. Overruns are counted by
+//!     // `thr::ThrBudget::meter` regardless of the hook.
+//!     budget => budget_overrun;
+//!
 //!     // Threads configuration.
 //!     threads => {
 //!         // Threads for exceptions.
@@ -48,14 +76,21 @@
 //!             // `sv_call_handler` directly to the vector table.
 //!             /// System service call.
 //!             pub naked(sv_call_handler) sv_call;
+//!             // Threads can declare a priority level and an enabled state. These settings are
+//!             // applied by `ThrsInitToken::init`. The level spans all implemented priority bits
+//!             // (see `thr::PRIORITY_BITS`), and is validated at compile time. The declared
+//!             // priority also defines ceilings of `thr::Resource`s.
+//!             // A thread can also declare an execution budget in processor cycles per
+//!             // activation.
 //!             /// System tick timer.
-//!             pub sys_tick;
+//!             pub sys_tick(priority = 1, budget = 2_000);
 //!         };
 //!         // Threads for interrupts.
 //!         interrupts => {
-//!             // Define a regular thread for the interrupt #5 with name `rcc`.
+//!             // Define a regular thread for the interrupt #5 with name `rcc`. The thread is
+//!             // disabled if it is activated more than 100 times within the `storm` window.
 //!             /// RCC global interrupt.
-//!             5: pub rcc;
+//!             5: pub rcc(priority = 2, enabled, rate = 100);
 //!             // Define an outer thread for the interrupt #18 with name `adc1`. This creates a
 //!             // thread token structure `Adc1`, a field `adc1` in the `Thrs` structure, and an
 //!             // element in the array of `Thr`. But unlike a regular thread, this outer thread
@@ -63,6 +98,19 @@
 //!             /// ADC1 global interrupt.
 //!             18: pub outer(adc1_handler) adc1;
 //!         };
+//!         // Virtual threads multiplexed onto a single physical vector, which is either
+//!         // `pend_sv` or an interrupt number not declared in the sections above. Waking a
+//!         // virtual thread pends the vector, and its handler resumes ready virtual threads
+//!         // in the order of their software priorities. Lower levels run first, and the
+//!         // declaration order breaks ties. Virtual threads don't preempt each other, and
+//!         // all of them run at the hardware priority of the vector, optionally set here.
+//!         // Each virtual thread gets a token implementing `thr::SoftToken`.
+//!         software(pend_sv, priority = 3) => {
+//!             /// Sensor data processing.
+//!             pub sensors(priority = 0);
+//!             /// Background logging.
+//!             pub logger(priority = 1);
+//!         };
 //!     };
 //! }
 //!
@@ -76,6 +124,15 @@
 //! unsafe extern "C" fn sv_call_handler() {}
 //! unsafe fn adc1_handler(_thr: &Thr) {}
 //!
+//! // Define the handler for unassigned interrupt vectors.
+//! fn unhandled_int(_int_num: u16) {}
+//!
+//! // Define the handler for interrupt storms.
+//! fn interrupt_storm(_int_num: u16) {}
+//!
+//! // Define the handler for execution budget overruns.
+//! fn budget_overrun(_thr_idx: u16, _cycles: u32) {}
+//!
 //! // Define and export the actual collection of exception vectors with all handlers attached.
 //! #[no_mangle]
 //! #[link_section = ".vectors.VECTORS"]
@@ -96,19 +153,50 @@
 //! * `pend_sv` - Pendable request for system service.
 //! * `sys_tick` - System tick timer.
 
+pub mod info;
 pub mod prelude;
+pub mod root;
+pub mod sync;
 
+mod budget;
+mod context;
+mod dyn_int;
+mod exc;
 mod init;
 mod int;
+mod int_set;
 mod nvic;
-mod root;
+mod priority;
+mod resource;
+mod soft;
+mod storm;
 mod wake;
 
-pub use self::init::{ThrInitExtended, ThrsInitToken};
+#[doc(hidden)]
+pub use self::budget::BudgetSpan;
+pub use self::budget::{BudgetMeter, ThrBudget};
+pub use self::context::{
+    current, execution_priority, in_interrupt, vect_active, ExecPriority, IntSnapshot, VectorMap,
+};
+pub use self::dyn_int::DynInt;
+pub use self::exc::{
+    ExcActive, ExcEnable, ExcPending, ExcPriority, ExcToken, ExcWakeup, Exception, ThrExc,
+};
+pub use self::init::{ThrConfig, ThrInitExtended, ThrsInitToken};
 pub use self::int::IntToken;
+pub use self::int_set::IntSet;
 pub use self::nvic::{NvicBlock, NvicIabr, NvicIcer, NvicIcpr, NvicIser, NvicIspr, ThrNvic};
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+pub use self::priority::{priority_grouping, set_priority_grouping};
+pub use self::priority::{Priority, PriorityGrouping, PRIORITY_BITS};
+pub use self::resource::{Resource, ResourceAccess, ResourceAccessBy, ThrPriority};
 pub use self::root::{FutureRootExt, StreamRootExt, StreamRootWait};
-use crate::map::reg::scb::Vtor;
+pub use self::soft::{SoftDispatcher, SoftThread, SoftToken, SoftVector, SOFT_THREAD_COUNT};
+#[doc(hidden)]
+pub use self::storm::{storm_check, storm_tick};
+pub use self::storm::{StormClock, StormCounter};
+use crate::map::reg::scb::{Icsr, Vtor};
+use crate::platform;
 use crate::reg::prelude::*;
 use crate::sv::Supervisor;
 use drone_core::thr::ThrToken;
@@ -121,12 +209,70 @@ use drone_core::token::Token;
 #[doc(inline)]
 pub use drone_cortexm_macros::thr_nvic as nvic;
 
+/// The maximum number of interrupts supported by the core.
+#[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+pub const INT_COUNT_MAX: u16 = 32;
+/// The maximum number of interrupts supported by the core.
+#[cfg(any(
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+))]
+pub const INT_COUNT_MAX: u16 = 480;
+/// The maximum number of interrupts supported by the core.
+#[cfg(not(any(
+    drone_cortexm = "cortexm0plus_r0p0",
+    drone_cortexm = "cortexm0plus_r0p1",
+    drone_cortexm = "cortexm33_r0p2",
+    drone_cortexm = "cortexm33_r0p3",
+    drone_cortexm = "cortexm33_r0p4",
+    drone_cortexm = "cortexm33f_r0p2",
+    drone_cortexm = "cortexm33f_r0p3",
+    drone_cortexm = "cortexm33f_r0p4",
+)))]
+pub const INT_COUNT_MAX: u16 = 240;
+
+#[doc(hidden)]
+pub const SECURITY_EXTENSION: bool = cfg!(feature = "security-extension");
+
+#[doc(hidden)]
+pub const CYCLE_COUNTER: bool =
+    !cfg!(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"));
+
 /// A trait to assign a supervisor to threads.
 pub trait ThrSv: ThrToken {
     /// The supervisor.
     type Sv: Supervisor;
 }
 
+#[doc(hidden)]
+#[inline]
+pub unsafe fn default_handler(handler: fn(u16)) {
+    let int_num = unsafe { Icsr::<Urt>::take().load().vectactive() } as u16 - 16;
+    unsafe { nvic::disable_int(int_num) };
+    handler(int_num);
+}
+
+#[doc(hidden)]
+#[inline]
+pub unsafe fn swap_vector(
+    table: *mut usize,
+    index: usize,
+    handler: Option<unsafe extern "C" fn()>,
+) -> Option<unsafe extern "C" fn()> {
+    platform::without_interrupts(|| unsafe {
+        let slot = table.add(index).cast::<Option<unsafe extern "C" fn()>>();
+        let prev = slot.read_volatile();
+        slot.write_volatile(handler);
+        // Ensures the new vector is observed by the next exception entry.
+        platform::regs::dsb();
+        prev
+    })
+}
+
 #[doc(hidden)]
 #[inline]
 pub unsafe fn relocate_vtable(copy: impl FnOnce(*const usize) -> *const usize) {
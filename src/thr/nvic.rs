@@ -1,13 +1,16 @@
-use crate::thr::IntToken;
+use crate::thr::priority::{read_priority, write_priority};
+use crate::thr::{IntToken, Priority, INT_COUNT_MAX};
 use core::marker::PhantomData;
 use core::ptr::{read_volatile, write_volatile};
 
-const NVIC_ISER: usize = 0xE000_E100;
-const NVIC_ICER: usize = 0xE000_E180;
-const NVIC_ISPR: usize = 0xE000_E200;
-const NVIC_ICPR: usize = 0xE000_E280;
-const NVIC_IABR: usize = 0xE000_E300;
-const NVIC_IPR: usize = 0xE000_E400;
+pub(crate) const NVIC_ISER: usize = 0xE000_E100;
+pub(crate) const NVIC_ICER: usize = 0xE000_E180;
+pub(crate) const NVIC_ISPR: usize = 0xE000_E200;
+pub(crate) const NVIC_ICPR: usize = 0xE000_E280;
+pub(crate) const NVIC_IABR: usize = 0xE000_E300;
+pub(crate) const NVIC_IPR: usize = 0xE000_E400;
+
+pub(crate) const NVIC_BLOCK_COUNT: usize = (INT_COUNT_MAX as usize + 31) / 32;
 
 macro_rules! nvic_reg {
     ($doc:expr, $name:ident, $base:expr) => {
@@ -195,14 +198,14 @@ pub trait ThrNvic: IntToken {
 
     /// Reads the priority of the interrupt.
     #[inline]
-    fn priority(self) -> u8 {
-        unsafe { read_volatile((NVIC_IPR as *const u8).add(Self::INT_NUM as usize)) }
+    fn priority(self) -> Priority {
+        unsafe { read_priority(NVIC_IPR + Self::INT_NUM as usize) }
     }
 
     /// Writes the priority of the interrupt.
     #[inline]
-    fn set_priority(self, priority: u8) {
-        unsafe { write_volatile((NVIC_IPR as *mut u8).add(Self::INT_NUM as usize), priority) };
+    fn set_priority(self, priority: Priority) {
+        unsafe { write_priority(NVIC_IPR + Self::INT_NUM as usize, priority) };
     }
 }
 
@@ -239,6 +242,16 @@ trait NvicReg<T: NvicBlock>: Sized {
 
 impl<T: IntToken> ThrNvic for T {}
 
+/// Disables the interrupt `int_num`.
+///
+/// # Safety
+///
+/// This function doesn't check for the interrupt token ownership.
+pub(crate) unsafe fn disable_int(int_num: u16) {
+    let icer = (NVIC_ICER as *mut u32).wrapping_add(usize::from(int_num >> 5));
+    unsafe { write_volatile(icer, 1 << (int_num & 0b1_1111)) };
+}
+
 const fn block_offset<T: IntToken>() -> usize {
     T::INT_NUM as usize & 0b1_1111
 }
@@ -10,11 +10,11 @@
 //! ```
 
 #[doc(no_inline)]
-pub use crate::thr::{IntToken, ThrsInitToken as _};
+pub use crate::thr::{ExcToken, IntToken, ThrsInitToken as _};
 #[doc(no_inline)]
 pub use crate::{
     fib::ThrFiberProc as _,
-    thr::{FutureRootExt as _, StreamRootExt as _, ThrNvic as _},
+    thr::{FutureRootExt as _, StreamRootExt as _, ThrExc as _, ThrNvic as _},
 };
 #[doc(no_inline)]
 pub use drone_core::thr::prelude::*;
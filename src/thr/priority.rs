@@ -0,0 +1,226 @@
+//! Exception and interrupt priorities.
+
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+use crate::map::reg::scb;
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+use crate::reg::prelude::*;
+use core::ptr::{read_volatile, write_volatile};
+
+/// The number of priority bits implemented by the MCU.
+///
+/// Taken from the `hardware.priority_bits` key of the Drone config. Defaults to
+/// the minimum allowed by the architecture: 2 for ARMv6-M, and 3 for ARMv7-M
+/// and ARMv8-M. ARMv6-M always implements 2 bits.
+pub const PRIORITY_BITS: u8 = parse_bits(env!("DRONE_CORTEXM_PRIORITY_BITS"));
+
+/// Exception or interrupt priority.
+///
+/// Lower values mean higher priorities. The value is stored in the raw 8-bit
+/// format of the priority registers, where only the [`PRIORITY_BITS`] most
+/// significant bits are implemented.
+///
+/// ```
+/// use drone_cortexm::thr::{Priority, PriorityGrouping, PRIORITY_BITS};
+///
+/// const GROUPING: PriorityGrouping = PriorityGrouping::new(1);
+/// const PRIORITY: Priority = Priority::new(0, 1, GROUPING);
+/// assert_eq!(PRIORITY.preempt(GROUPING), 0);
+/// assert_eq!(PRIORITY.sub(GROUPING), 1);
+/// assert_eq!(Priority::from_level(1).to_raw(), 1 << (8 - PRIORITY_BITS));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Priority(u8);
+
+/// Split of the priority bits into preemption priority and sub-priority.
+///
+/// Corresponds to the `AIRCR.PRIGROUP` field. Only the preemption priority
+/// determines whether an exception can preempt another one. The sub-priority
+/// orders pending exceptions of the same preemption priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PriorityGrouping {
+    sub_bits: u8,
+}
+
+impl Priority {
+    /// The highest priority.
+    pub const HIGHEST: Self = Self(0);
+
+    /// The lowest priority.
+    pub const LOWEST: Self = Self::from_raw(0xFF);
+
+    /// Creates a priority from the preemption level `preempt` and the
+    /// sub-priority level `sub` according to the `grouping`.
+    ///
+    /// # Panics
+    ///
+    /// If any of the levels doesn't fit into its number of bits. In constant
+    /// contexts this is a compile-time error.
+    #[inline]
+    pub const fn new(preempt: u8, sub: u8, grouping: PriorityGrouping) -> Self {
+        assert!((preempt as u16) < 1 << grouping.preempt_bits(), "preempt level is out of range");
+        assert!((sub as u16) < 1 << grouping.sub_bits(), "sub-priority level is out of range");
+        Self::from_level(((preempt as u16) << grouping.sub_bits() | sub as u16) as u8)
+    }
+
+    /// Creates a priority from the `level` spanning all implemented bits.
+    ///
+    /// # Panics
+    ///
+    /// If the level doesn't fit into [`PRIORITY_BITS`]. In constant contexts
+    /// this is a compile-time error.
+    #[inline]
+    pub const fn from_level(level: u8) -> Self {
+        assert!((level as u16) < 1 << PRIORITY_BITS, "priority level is out of range");
+        Self(((level as u16) << (8 - PRIORITY_BITS)) as u8)
+    }
+
+    /// Creates a priority from the raw register value, discarding the
+    /// unimplemented bits.
+    #[inline]
+    pub const fn from_raw(raw: u8) -> Self {
+        Self(raw & implemented_mask())
+    }
+
+    /// Returns the raw register value.
+    #[inline]
+    pub const fn to_raw(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the level spanning all implemented bits.
+    #[inline]
+    pub const fn level(self) -> u8 {
+        self.0 >> (8 - PRIORITY_BITS)
+    }
+
+    /// Returns the preemption level according to the `grouping`.
+    #[inline]
+    pub const fn preempt(self, grouping: PriorityGrouping) -> u8 {
+        ((self.level() as u16) >> grouping.sub_bits()) as u8
+    }
+
+    /// Returns the sub-priority level according to the `grouping`.
+    #[inline]
+    pub const fn sub(self, grouping: PriorityGrouping) -> u8 {
+        ((self.level() as u16) & ((1 << grouping.sub_bits()) - 1)) as u8
+    }
+}
+
+impl PriorityGrouping {
+    /// The grouping with the maximum number of preemption bits.
+    pub const MAX_PREEMPT: Self = Self { sub_bits: if PRIORITY_BITS == 8 { 1 } else { 0 } };
+
+    /// Creates a grouping with `sub_bits` of the implemented bits used for
+    /// sub-priority.
+    ///
+    /// # Panics
+    ///
+    /// If `sub_bits` exceeds [`PRIORITY_BITS`], or if all 8 bits are
+    /// implemented and `sub_bits` is zero. In constant contexts this is a
+    /// compile-time error.
+    #[inline]
+    pub const fn new(sub_bits: u8) -> Self {
+        assert!(sub_bits <= PRIORITY_BITS, "sub-priority bits exceed implemented bits");
+        assert!(sub_bits + 8 - PRIORITY_BITS > 0, "at least one sub-priority bit is required");
+        Self { sub_bits }
+    }
+
+    /// Creates a grouping from the `AIRCR.PRIGROUP` field value.
+    #[inline]
+    pub const fn from_prigroup(prigroup: u8) -> Self {
+        let sub_bits = ((prigroup & 0b111) + 1).saturating_sub(8 - PRIORITY_BITS);
+        Self { sub_bits }
+    }
+
+    /// Returns the `AIRCR.PRIGROUP` field value.
+    #[inline]
+    pub const fn prigroup(self) -> u8 {
+        // Sub-priority occupies bits [PRIGROUP:0].
+        (self.sub_bits + 8 - PRIORITY_BITS) - 1
+    }
+
+    /// Returns the number of implemented bits used for preemption priority.
+    #[inline]
+    pub const fn preempt_bits(self) -> u8 {
+        PRIORITY_BITS - self.sub_bits
+    }
+
+    /// Returns the number of implemented bits used for sub-priority.
+    #[inline]
+    pub const fn sub_bits(self) -> u8 {
+        self.sub_bits
+    }
+}
+
+/// Writes the priority grouping to `AIRCR.PRIGROUP`.
+///
+/// **NOTE** Changing the grouping while exceptions are active can lead to
+/// unpredictable preemption.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+#[inline]
+pub fn set_priority_grouping(aircr: &scb::Aircr<Srt>, grouping: PriorityGrouping) {
+    let mut aircr_val = aircr.load();
+    aircr_val.write_vectkey(0x05FA).write_prigroup(u32::from(grouping.prigroup()));
+    aircr.store_val(aircr_val.val());
+}
+
+/// Reads the priority grouping from `AIRCR.PRIGROUP`.
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+#[inline]
+pub fn priority_grouping(aircr: &scb::Aircr<Srt>) -> PriorityGrouping {
+    PriorityGrouping::from_prigroup(aircr.load().prigroup() as u8)
+}
+
+/// Reads the priority byte at `addr` of `NVIC_IPRn` or `SHPRn` registers.
+///
+/// ARMv6-M supports only word accesses to these registers.
+///
+/// # Safety
+///
+/// `addr` must point to a priority byte.
+#[inline]
+pub(crate) unsafe fn read_priority(addr: usize) -> Priority {
+    #[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+    let raw = unsafe { read_volatile(addr as *const u8) };
+    #[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+    let raw = (unsafe { read_volatile((addr & !0b11) as *const u32) } >> byte_shift(addr)) as u8;
+    Priority::from_raw(raw)
+}
+
+/// Writes the priority byte at `addr` of `NVIC_IPRn` or `SHPRn` registers.
+///
+/// On ARMv6-M the byte is written with a read-modify-write of the whole word
+/// inside a critical section.
+///
+/// # Safety
+///
+/// `addr` must point to a priority byte.
+#[inline]
+pub(crate) unsafe fn write_priority(addr: usize, priority: Priority) {
+    #[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+    unsafe {
+        write_volatile(addr as *mut u8, priority.to_raw());
+    }
+    #[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+    crate::platform::without_interrupts(|| unsafe {
+        let word = (addr & !0b11) as *mut u32;
+        let shift = byte_shift(addr);
+        let value = read_volatile(word) & !(0xFF << shift) | u32::from(priority.to_raw()) << shift;
+        write_volatile(word, value);
+    });
+}
+
+#[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+const fn byte_shift(addr: usize) -> u32 {
+    (addr as u32 & 0b11) * 8
+}
+
+const fn implemented_mask() -> u8 {
+    !((1 << (8 - PRIORITY_BITS)) - 1)
+}
+
+const fn parse_bits(bits: &str) -> u8 {
+    let bits = bits.as_bytes();
+    assert!(bits.len() == 1 && bits[0] >= b'2' && bits[0] <= b'8', "invalid priority bits");
+    bits[0] - b'0'
+}
@@ -0,0 +1,152 @@
+use crate::platform::critical::with_priority;
+use crate::thr::Priority;
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use drone_core::thr::ThrToken;
+
+/// A thread token with a priority declared in [`thr::nvic!`](crate::thr::nvic).
+pub trait ThrPriority: ThrToken {
+    /// The declared priority of the thread.
+    const PRIORITY: Priority;
+}
+
+/// A tuple of thread tokens allowed to access a [`Resource`].
+pub trait ResourceAccess {
+    /// The highest priority of the threads.
+    const CEILING: Priority;
+}
+
+/// A marker trait for tuples of thread tokens containing `T`.
+#[marker]
+pub trait ResourceAccessBy<T: ThrPriority>: ResourceAccess {}
+
+/// A resource shared between threads according to the Stack Resource Policy.
+///
+/// The threads allowed to access the resource are listed in the tuple `A`. The
+/// resource ceiling is the highest priority of these threads, and locking the
+/// resource raises the execution priority to the ceiling with
+/// [`critical::with_priority`](crate::platform::critical::with_priority).
+/// Therefore the lock never blocks, and the sharing is deadlock-free.
+///
+/// The priorities are taken from [`thr::nvic!`](crate::thr::nvic) declarations,
+/// so the actual priorities must not be changed at run time.
+///
+/// ```no_run
+/// # #![feature(const_fn_fn_ptr_basics)]
+/// # #![feature(marker_trait_attr)]
+/// # use drone_cortexm::map::thr::*;
+/// # use drone_cortexm::thr;
+/// # thr::nvic! {
+/// #     thread => pub Thr {};
+/// #     local => pub ThrLocal {};
+/// #     index => pub Thrs;
+/// #     vectors => pub Vectors;
+/// #     init => pub ThrsInit;
+/// #     threads => {
+/// #         exceptions => {
+/// #             pub sys_tick(priority = 2);
+/// #         };
+/// #         interrupts => {
+/// #             5: pub rcc(priority = 1);
+/// #         };
+/// #     };
+/// # }
+/// use drone_cortexm::thr::Resource;
+///
+/// static COUNTER: Resource<u32, (SysTick, Rcc)> = Resource::new(0);
+///
+/// fn handle_rcc(rcc: Rcc) {
+///     COUNTER.lock(rcc, |counter| *counter += 1);
+/// }
+/// # fn main() {}
+/// ```
+pub struct Resource<T, A: ResourceAccess> {
+    data: UnsafeCell<T>,
+    locked: UnsafeCell<bool>,
+    _access: PhantomData<fn() -> A>,
+}
+
+unsafe impl<T: Send, A: ResourceAccess> Sync for Resource<T, A> {}
+
+impl<T, A: ResourceAccess> Resource<T, A> {
+    /// The resource ceiling.
+    pub const CEILING: Priority = A::CEILING;
+
+    /// Creates a new resource.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self { data: UnsafeCell::new(value), locked: UnsafeCell::new(false), _access: PhantomData }
+    }
+
+    /// Runs the closure `f` with exclusive access to the resource from the
+    /// thread `token`.
+    ///
+    /// # Panics
+    ///
+    /// If the resource is already locked by the same thread.
+    #[inline]
+    pub fn lock<U: ThrPriority, R>(&self, _token: U, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        A: ResourceAccessBy<U>,
+    {
+        with_priority(Self::CEILING, || {
+            // Only the current thread can access the resource at the ceiling.
+            let locked = unsafe { &mut *self.locked.get() };
+            assert!(!*locked, "resource is already locked");
+            *locked = true;
+            let result = f(unsafe { &mut *self.data.get() });
+            *locked = false;
+            result
+        })
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Consumes the resource, returning the underlying data.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+macro_rules! resource_access {
+    ($($ty:ident),+) => {
+        impl<$($ty: ThrPriority),+> ResourceAccess for ($($ty,)+) {
+            const CEILING: Priority = ceiling(&[$($ty::PRIORITY),+]);
+        }
+
+        resource_access!(@by [$($ty),+] $($ty),+);
+    };
+    (@by $all:tt $($ty:ident),+) => {
+        $(resource_access!(@by_one $all $ty);)+
+    };
+    (@by_one [$($all:ident),+] $ty:ident) => {
+        impl<$($all: ThrPriority),+> ResourceAccessBy<$ty> for ($($all,)+) {}
+    };
+}
+
+resource_access!(A);
+resource_access!(A, B);
+resource_access!(A, B, C);
+resource_access!(A, B, C, D);
+resource_access!(A, B, C, D, E);
+resource_access!(A, B, C, D, E, F);
+resource_access!(A, B, C, D, E, F, G);
+resource_access!(A, B, C, D, E, F, G, H);
+
+const fn ceiling(priorities: &[Priority]) -> Priority {
+    let mut ceiling = Priority::LOWEST;
+    let mut i = 0;
+    while i < priorities.len() {
+        // Lower values mean higher priorities.
+        if priorities[i].to_raw() < ceiling.to_raw() {
+            ceiling = priorities[i];
+        }
+        i += 1;
+    }
+    ceiling
+}
@@ -0,0 +1,117 @@
+use crate::drv::timer::Tickless;
+use crate::platform;
+use crate::thr::wake::{WakeRoot, WakeRootTask, TASK_COUNT};
+use alloc::boxed::Box;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::Context;
+
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// An executor of multiple futures on the lowest priority thread.
+///
+/// Each task has its own wake flag, so only the woken tasks are polled after
+/// the processor leaves `WFE`. Up to 32 tasks can be spawned, and only one
+/// executor can exist at a time.
+///
+/// **WARNING** [`run`](Executor::run) will block currently preempted threads.
+/// It is recommended to use the executor only on the lowest priority thread.
+pub struct Executor<'a> {
+    tasks: [Option<Pin<Box<dyn Future<Output = ()> + 'a>>>; TASK_COUNT],
+}
+
+impl<'a> Executor<'a> {
+    /// Creates a new executor.
+    ///
+    /// # Panics
+    ///
+    /// If another executor exists.
+    pub fn new() -> Self {
+        let taken = platform::without_interrupts(|| {
+            let taken = TAKEN.load(Ordering::Relaxed);
+            TAKEN.store(true, Ordering::Relaxed);
+            taken
+        });
+        assert!(!taken, "root executor already exists");
+        Self { tasks: [(); TASK_COUNT].map(|()| None) }
+    }
+
+    /// Adds a task to the executor. The task is polled for the first time on
+    /// the next [`run`](Executor::run).
+    ///
+    /// # Panics
+    ///
+    /// If the executor already has 32 tasks.
+    pub fn spawn(&mut self, future: impl Future<Output = ()> + 'a) {
+        let (task, slot) = self
+            .tasks
+            .iter_mut()
+            .enumerate()
+            .find(|(_, slot)| slot.is_none())
+            .expect("root executor is full");
+        *slot = Some(Box::pin(future));
+        WakeRootTask::new(task).wakeup();
+    }
+
+    /// Returns the number of incomplete tasks.
+    pub fn len(&self) -> usize {
+        self.tasks.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    /// Returns `true` if all tasks are complete.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.iter().all(Option::is_none)
+    }
+
+    /// Runs all tasks to completion.
+    pub fn run(&mut self) {
+        self.run_with(WakeRoot::wait);
+    }
+
+    /// Runs all tasks to completion, stretching the periodic tick of `timer`
+    /// while idle.
+    ///
+    /// See [`FutureRootExt::root_wait_tickless`](super::FutureRootExt) for
+    /// details.
+    pub fn run_tickless<U: Tickless>(&mut self, timer: &U) {
+        self.run_with(|| WakeRoot::wait_tickless(timer));
+    }
+
+    fn run_with(&mut self, mut wait: impl FnMut()) {
+        while !self.is_empty() {
+            let mut polled = false;
+            for (task, slot) in self.tasks.iter_mut().enumerate() {
+                let Some(future) = slot else { continue };
+                let wake = WakeRootTask::new(task);
+                if !wake.take() {
+                    continue;
+                }
+                polled = true;
+                let waker = wake.to_waker();
+                if future.as_mut().poll(&mut Context::from_waker(&waker)).is_ready() {
+                    *slot = None;
+                }
+            }
+            // Tasks woken during polling are polled again without waiting.
+            if !polled {
+                wait();
+            }
+        }
+    }
+}
+
+impl Default for Executor<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Executor<'_> {
+    fn drop(&mut self) {
+        for task in 0..TASK_COUNT {
+            WakeRootTask::new(task).take();
+        }
+        TAKEN.store(false, Ordering::Relaxed);
+    }
+}
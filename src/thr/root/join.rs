@@ -0,0 +1,154 @@
+use core::future::Future;
+use core::mem;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures::future::Either;
+
+/// Future returned from [`join`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Join<A: Future, B: Future> {
+    a: MaybeDone<A>,
+    b: MaybeDone<B>,
+}
+
+/// Future returned from [`select`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Select<A, B> {
+    a: A,
+    b: B,
+}
+
+enum MaybeDone<F: Future> {
+    Pending(F),
+    Done(F::Output),
+    Taken,
+}
+
+/// Runs two futures concurrently, and completes with both outputs when both
+/// of them are complete.
+pub fn join<A: Future, B: Future>(a: A, b: B) -> Join<A, B> {
+    Join { a: MaybeDone::Pending(a), b: MaybeDone::Pending(b) }
+}
+
+/// Runs two futures concurrently, and completes with the output of the first
+/// one to complete. The other future is dropped.
+///
+/// If both futures are ready, `a` takes precedence.
+pub fn select<A: Future, B: Future>(a: A, b: B) -> Select<A, B> {
+    Select { a, b }
+}
+
+impl<A: Future, B: Future> Future for Join<A, B> {
+    type Output = (A::Output, B::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Fields are never moved out while pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let a = unsafe { Pin::new_unchecked(&mut this.a) }.poll(cx);
+        let b = unsafe { Pin::new_unchecked(&mut this.b) }.poll(cx);
+        if a && b { Poll::Ready((this.a.take(), this.b.take())) } else { Poll::Pending }
+    }
+}
+
+impl<A: Future, B: Future> Future for Select<A, B> {
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Fields are never moved out while pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Poll::Ready(output) = unsafe { Pin::new_unchecked(&mut this.a) }.poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+        if let Poll::Ready(output) = unsafe { Pin::new_unchecked(&mut this.b) }.poll(cx) {
+            return Poll::Ready(Either::Right(output));
+        }
+        Poll::Pending
+    }
+}
+
+impl<F: Future> MaybeDone<F> {
+    /// Polls the future if it's pending, and returns `true` if it's done.
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> bool {
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Self::Pending(future) = this {
+            match unsafe { Pin::new_unchecked(future) }.poll(cx) {
+                Poll::Ready(output) => *this = Self::Done(output),
+                Poll::Pending => return false,
+            }
+        }
+        true
+    }
+
+    fn take(&mut self) -> F::Output {
+        match mem::replace(self, Self::Taken) {
+            Self::Done(output) => output,
+            _ => panic!("`Join` polled after completion"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::pin::pin;
+    use futures::future::{self, poll_fn};
+    use futures::task::noop_waker_ref;
+
+    /// Returns a future, which completes with `output` on `polls`-th poll.
+    fn after(polls: usize, output: u32, count: &Cell<usize>) -> impl Future<Output = u32> + '_ {
+        poll_fn(move |_| {
+            count.set(count.get() + 1);
+            if count.get() < polls { Poll::Pending } else { Poll::Ready(output) }
+        })
+    }
+
+    #[test]
+    fn join_interleaving() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let (a_polls, b_polls) = (Cell::new(0), Cell::new(0));
+        let mut future = pin!(join(after(2, 1, &a_polls), after(3, 2, &b_polls)));
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready((1, 2)));
+        // A complete future is not polled again.
+        assert_eq!((a_polls.get(), b_polls.get()), (2, 3));
+    }
+
+    #[test]
+    fn join_ready() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let future = pin!(join(future::ready(1), future::ready(2)));
+        assert_eq!(future.poll(&mut cx), Poll::Ready((1, 2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "`Join` polled after completion")]
+    fn join_after_completion() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let mut future = pin!(join(future::ready(1), future::ready(2)));
+        assert!(future.as_mut().poll(&mut cx).is_ready());
+        let _ = future.as_mut().poll(&mut cx);
+    }
+
+    #[test]
+    fn select_interleaving() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let (a_polls, b_polls) = (Cell::new(0), Cell::new(0));
+        let mut future = pin!(select(after(3, 1, &a_polls), after(2, 2, &b_polls)));
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+        assert!(matches!(future.as_mut().poll(&mut cx), Poll::Ready(Either::Right(2))));
+        assert_eq!((a_polls.get(), b_polls.get()), (2, 2));
+    }
+
+    #[test]
+    fn select_precedence() {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let b_polls = Cell::new(0);
+        let future = pin!(select(future::ready(1), after(1, 2, &b_polls)));
+        assert!(matches!(future.poll(&mut cx), Poll::Ready(Either::Left(1))));
+        assert_eq!(b_polls.get(), 0);
+        let future = pin!(select(future::pending::<u32>(), future::ready(2)));
+        assert!(matches!(future.poll(&mut cx), Poll::Ready(Either::Right(2))));
+    }
+}
@@ -1,3 +1,29 @@
+//! The lowest priority context.
+//!
+//! The root context is the code running in Thread mode, e.g. `main`. Futures
+//! are driven there by blocking the processor with `WFE` until one of them is
+//! woken:
+//!
+//! ```no_run
+//! use drone_cortexm::thr::root::{self, Executor};
+//!
+//! async fn housekeeping() {}
+//! async fn watchdog() {}
+//!
+//! let mut executor = Executor::new();
+//! executor.spawn(housekeeping());
+//! executor.spawn(async {
+//!     let ((), ()) = root::join(watchdog(), async {}).await;
+//! });
+//! executor.run();
+//! ```
+
+mod executor;
+mod join;
+
+pub use self::executor::Executor;
+pub use self::join::{join, select, Join, Select};
+use crate::drv::timer::Tickless;
 use crate::thr::wake::WakeRoot;
 use core::future::Future;
 use core::iter::FusedIterator;
@@ -16,6 +42,17 @@ pub trait FutureRootExt: Future {
     /// **WARNING** This method will block currently preempted threads. It is
     /// recommended to use this method only on the lowest priority thread.
     fn root_wait(self) -> Self::Output;
+
+    /// Runs a future to completion on the lowest priority thread, stretching
+    /// the periodic tick of `timer` while idle.
+    ///
+    /// Before each sleep the `timer` is asked for the next deadline, and is
+    /// reprogrammed to cover the whole idle span with a single event. This
+    /// avoids waking up the core on each tick on battery-powered devices.
+    ///
+    /// **WARNING** This method will block currently preempted threads. It is
+    /// recommended to use this method only on the lowest priority thread.
+    fn root_wait_tickless<U: Tickless>(self, timer: &U) -> Self::Output;
 }
 
 /// An extension trait for [`Stream`] that provides
@@ -56,6 +93,17 @@ impl<T: Future> FutureRootExt for T {
             }
         }
     }
+
+    fn root_wait_tickless<U: Tickless>(mut self, timer: &U) -> Self::Output {
+        let waker = WakeRoot::new().to_waker();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match unsafe { Pin::new_unchecked(&mut self) }.poll(&mut cx) {
+                Poll::Pending => WakeRoot::wait_tickless(timer),
+                Poll::Ready(value) => break value,
+            }
+        }
+    }
 }
 
 impl<'a, T: Stream> StreamRootExt<'a> for T {
@@ -0,0 +1,229 @@
+use crate::platform::without_interrupts;
+#[cfg(not(feature = "host"))]
+use crate::thr::wake::{WakeInt, WakePendSv};
+use core::cell::UnsafeCell;
+use core::ptr;
+#[cfg(feature = "host")]
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{RawWaker, RawWakerVTable, Waker};
+use drone_core::thr::ThrToken;
+
+/// The maximum number of virtual threads sharing one vector.
+pub const SOFT_THREAD_COUNT: usize = 32;
+
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake, drop);
+
+/// A token of a virtual thread declared in the `software` section of
+/// [`nvic!`](crate::thr::nvic).
+pub trait SoftToken: ThrToken {
+    /// The priority rank of the thread within its dispatcher. `0` is the
+    /// highest.
+    const SOFT_RANK: u32;
+
+    /// Returns the virtual thread.
+    fn soft_thread() -> &'static SoftThread;
+}
+
+/// A physical vector multiplexing virtual threads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoftVector {
+    /// The `pend_sv` exception.
+    PendSv,
+    /// The interrupt with the given number.
+    Int(u16),
+}
+
+/// A dispatcher of virtual threads sharing one physical vector.
+///
+/// Each virtual thread is represented by a bit in the ready mask. The bit
+/// position is the software priority rank of the thread, with the bit `0`
+/// being the highest priority. The dispatcher runs in the handler of the
+/// physical vector, and resumes ready threads one by one, always picking the
+/// highest priority one. Virtual threads sharing a vector don't preempt each
+/// other.
+pub struct SoftDispatcher {
+    vector: SoftVector,
+    ready: UnsafeCell<u32>,
+    // The physical vector can't be pended on the host, so it's recorded here.
+    #[cfg(feature = "host")]
+    pended: AtomicBool,
+}
+
+/// A virtual thread bound to a [`SoftDispatcher`].
+pub struct SoftThread {
+    dispatcher: &'static SoftDispatcher,
+    mask: u32,
+}
+
+unsafe impl Sync for SoftDispatcher {}
+
+impl SoftDispatcher {
+    /// Creates a new dispatcher for the physical `vector`.
+    #[inline]
+    pub const fn new(vector: SoftVector) -> Self {
+        Self {
+            vector,
+            ready: UnsafeCell::new(0),
+            #[cfg(feature = "host")]
+            pended: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the physical vector of the dispatcher.
+    #[inline]
+    pub fn vector(&self) -> SoftVector {
+        self.vector
+    }
+
+    /// Returns `true` if there are virtual threads ready to run.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        without_interrupts(|| unsafe { *self.ready.get() } != 0)
+    }
+
+    /// Runs ready virtual threads in priority order, until none is left.
+    /// `run` is called with the priority rank of each thread.
+    ///
+    /// # Safety
+    ///
+    /// Must be called only from the handler of the physical vector.
+    #[inline]
+    pub unsafe fn dispatch(&self, mut run: impl FnMut(u32)) {
+        while let Some(rank) = self.take() {
+            run(rank);
+        }
+    }
+
+    fn take(&self) -> Option<u32> {
+        without_interrupts(|| {
+            let ready = unsafe { &mut *self.ready.get() };
+            (*ready != 0).then(|| {
+                let rank = ready.trailing_zeros();
+                *ready &= !(1 << rank);
+                rank
+            })
+        })
+    }
+
+    fn pend(&self, mask: u32) {
+        without_interrupts(|| unsafe { *self.ready.get() |= mask });
+        #[cfg(feature = "host")]
+        self.pended.store(true, Ordering::Relaxed);
+        #[cfg(not(feature = "host"))]
+        match self.vector {
+            SoftVector::PendSv => WakePendSv::wakeup(),
+            SoftVector::Int(int_num) => WakeInt::new(int_num).wakeup(),
+        }
+    }
+}
+
+impl SoftThread {
+    /// Creates a new virtual thread with the priority `rank` in `dispatcher`.
+    ///
+    /// # Panics
+    ///
+    /// If `rank` is not less than [`SOFT_THREAD_COUNT`].
+    #[inline]
+    pub const fn new(dispatcher: &'static SoftDispatcher, rank: u32) -> Self {
+        assert!((rank as usize) < SOFT_THREAD_COUNT, "too many virtual threads");
+        Self { dispatcher, mask: 1 << rank }
+    }
+
+    /// Returns the priority rank of the thread within its dispatcher.
+    #[inline]
+    pub fn rank(&self) -> u32 {
+        self.mask.trailing_zeros()
+    }
+
+    /// Marks the thread ready and pends the physical vector.
+    #[inline]
+    pub fn wakeup(&self) {
+        self.dispatcher.pend(self.mask);
+    }
+
+    /// Returns a handle for waking up the thread.
+    #[inline]
+    pub fn to_waker(&'static self) -> Waker {
+        unsafe { Waker::from_raw(self.to_raw_waker()) }
+    }
+
+    fn to_raw_waker(&'static self) -> RawWaker {
+        RawWaker::new(ptr::from_ref(self).cast(), &VTABLE)
+    }
+}
+
+unsafe fn clone(data: *const ()) -> RawWaker {
+    unsafe { &*data.cast::<SoftThread>() }.to_raw_waker()
+}
+
+unsafe fn wake(data: *const ()) {
+    unsafe { &*data.cast::<SoftThread>() }.wakeup();
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    impl SoftDispatcher {
+        fn take_pended(&self) -> bool {
+            self.pended.swap(false, Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn dispatch_order() {
+        static DISPATCHER: SoftDispatcher = SoftDispatcher::new(SoftVector::PendSv);
+        static THREADS: [SoftThread; 3] = [
+            SoftThread::new(&DISPATCHER, 0),
+            SoftThread::new(&DISPATCHER, 3),
+            SoftThread::new(&DISPATCHER, 31),
+        ];
+        THREADS[2].wakeup();
+        THREADS[0].wakeup();
+        THREADS[1].to_waker().wake();
+        assert!(DISPATCHER.is_ready());
+        let mut ranks = Vec::new();
+        unsafe { DISPATCHER.dispatch(|rank| ranks.push(rank)) };
+        assert_eq!(ranks, [0, 3, 31]);
+        assert!(!DISPATCHER.is_ready());
+    }
+
+    #[test]
+    fn dispatch_wakeup() {
+        static DISPATCHER: SoftDispatcher = SoftDispatcher::new(SoftVector::Int(5));
+        static THREADS: [SoftThread; 3] = [
+            SoftThread::new(&DISPATCHER, 0),
+            SoftThread::new(&DISPATCHER, 1),
+            SoftThread::new(&DISPATCHER, 2),
+        ];
+        THREADS[1].wakeup();
+        let mut ranks = Vec::new();
+        unsafe {
+            DISPATCHER.dispatch(|rank| {
+                ranks.push(rank);
+                // Threads woken while another one runs are resumed in the same
+                // dispatch, in priority order.
+                if ranks.len() == 1 {
+                    THREADS[2].wakeup();
+                    THREADS[1].wakeup();
+                    THREADS[0].wakeup();
+                }
+            });
+        }
+        assert_eq!(ranks, [1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn wakeup_pends_vector() {
+        static DISPATCHER: SoftDispatcher = SoftDispatcher::new(SoftVector::PendSv);
+        static THREAD: SoftThread = SoftThread::new(&DISPATCHER, 4);
+        assert!(!DISPATCHER.take_pended());
+        THREAD.wakeup();
+        assert!(DISPATCHER.take_pended());
+        THREAD.to_waker().wake_by_ref();
+        assert!(DISPATCHER.take_pended());
+        unsafe { DISPATCHER.dispatch(|rank| assert_eq!(rank, 4)) };
+        assert!(!DISPATCHER.take_pended());
+    }
+}
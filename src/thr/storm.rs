@@ -0,0 +1,147 @@
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
+use crate::platform::dwt_cycles;
+use crate::thr::nvic;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+static SYS_TICKS: AtomicU32 = AtomicU32::new(0);
+
+/// A time source measuring interrupt storm detection windows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StormClock {
+    /// Processor cycles counted by `DWT_CYCCNT`. The counter must be enabled
+    /// with `DWT_CTRL.CYCCNTENA`, e.g. by
+    /// [`drv::dwt::CycleTimer`](crate::drv::dwt::CycleTimer). Not available
+    /// on ARMv6-M.
+    // Kept on ARMv6-M for `thr::nvic!`, which rejects it with a compile-time
+    // assertion.
+    #[cfg_attr(
+        any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"),
+        doc(hidden)
+    )]
+    Dwt,
+    /// Periods of SysTick counted by the handler of the `sys_tick` thread.
+    SysTick,
+}
+
+/// An activation counter of a rate-limited interrupt thread.
+///
+/// The counter opens a window on the first activation, and counts
+/// activations until the window elapses.
+pub struct StormCounter {
+    state: UnsafeCell<State>,
+}
+
+struct State {
+    start: u32,
+    count: u32,
+}
+
+unsafe impl Sync for StormCounter {}
+
+impl StormClock {
+    /// Returns the current time of the clock.
+    #[inline]
+    pub fn now(self) -> u32 {
+        match self {
+            #[cfg(not(any(
+                drone_cortexm = "cortexm0plus_r0p0",
+                drone_cortexm = "cortexm0plus_r0p1"
+            )))]
+            Self::Dwt => dwt_cycles(),
+            #[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+            Self::Dwt => unreachable!(),
+            Self::SysTick => SYS_TICKS.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl StormCounter {
+    /// Creates a new counter.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { state: UnsafeCell::new(State { start: 0, count: 0 }) }
+    }
+
+    /// Counts an activation at the time `now`. Returns `true` if there were
+    /// more than `rate` activations within `window` units of time.
+    ///
+    /// # Safety
+    ///
+    /// Must be called only from the handler of the thread owning the counter.
+    #[inline]
+    pub unsafe fn hit(&self, now: u32, window: u32, rate: u32) -> bool {
+        let state = unsafe { &mut *self.state.get() };
+        if state.count == 0 || now.wrapping_sub(state.start) >= window {
+            state.start = now;
+            state.count = 0;
+        }
+        state.count = state.count.saturating_add(1);
+        state.count > rate
+    }
+
+    /// Resets the counter, so that a new window opens on the next activation.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called while the handler of the thread owning the counter
+    /// can run, e.g. before the interrupt is re-enabled.
+    #[inline]
+    pub unsafe fn reset(&self) {
+        unsafe { (*self.state.get()).count = 0 };
+    }
+}
+
+impl Default for StormCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn storm_tick() {
+    // The `sys_tick` handler is the only writer.
+    SYS_TICKS.store(SYS_TICKS.load(Ordering::Relaxed).wrapping_add(1), Ordering::Relaxed);
+}
+
+#[doc(hidden)]
+#[inline]
+pub unsafe fn storm_check(
+    counter: &StormCounter,
+    int_num: u16,
+    clock: StormClock,
+    window: u32,
+    rate: u32,
+    hook: fn(u16),
+) -> bool {
+    if unsafe { counter.hit(clock.now(), window, rate) } {
+        unsafe {
+            nvic::disable_int(int_num);
+            counter.reset();
+        }
+        hook(int_num);
+        false
+    } else {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counter_window() {
+        let counter = StormCounter::new();
+        unsafe {
+            assert!(!counter.hit(u32::MAX - 5, 10, 2));
+            assert!(!counter.hit(u32::MAX, 10, 2));
+            assert!(counter.hit(3, 10, 2));
+            assert!(!counter.hit(4, 10, 2));
+            counter.reset();
+            assert!(!counter.hit(5, 10, 2));
+            assert!(!counter.hit(6, 10, 2));
+        }
+    }
+}
@@ -0,0 +1,226 @@
+//! Synchronization primitives for threads of different priorities.
+//!
+//! The primitives don't depend on the `atomics` feature, and can be used on
+//! ARMv6-M. The shared state is protected by short `PRIMASK` critical
+//! sections. The SPSC channel transfers values without them, and enters a
+//! critical section only to exchange the waker of a blocked half.
+//!
+//! Up to [`WAIT_LIST_CAPACITY`] tasks can wait for a [`Mutex`], a
+//! [`Semaphore`], or capacity in an [`mpsc`] channel at once. Excess tasks
+//! are woken right away, and poll again.
+//!
+//! A blocked future is woken through the waker of its task. For fibers running
+//! on an interrupt thread this waker pends the interrupt with `STIR` or
+//! `NVIC_ISPR`, so the waiting thread resumes at its own priority.
+//!
+//! The primitives are created in `static`s:
+//!
+//! ```no_run
+//! use drone_cortexm::thr::sync::{mpsc, Mutex, Semaphore};
+//!
+//! static EVENTS: mpsc::Channel<u32, 8> = mpsc::Channel::new();
+//! static STATE: Mutex<u32> = Mutex::new(0);
+//! static SLOTS: Semaphore = Semaphore::new(2);
+//!
+//! async fn handle() {
+//!     let (mut tx, mut rx) = EVENTS.split();
+//!     tx.send(1).await.unwrap();
+//!     assert_eq!(rx.recv().await, Some(1));
+//!     *STATE.lock().await += 1;
+//!     let _permit = SLOTS.acquire().await;
+//! }
+//! ```
+
+pub mod mpsc;
+pub mod spsc;
+
+mod mutex;
+mod semaphore;
+
+pub use self::mutex::{Mutex, MutexGuard};
+pub use self::semaphore::{Semaphore, SemaphorePermit};
+use crate::platform::without_interrupts;
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::{self, MaybeUninit};
+use core::task::Waker;
+
+/// The maximum number of tasks waiting for a resource at once.
+pub const WAIT_LIST_CAPACITY: usize = 8;
+
+/// Error returned from `send` methods when the receiver is dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+/// Error returned from `try_send` methods.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The channel is full.
+    Full(T),
+    /// The receiver is dropped.
+    Closed(T),
+}
+
+/// Error returned from `try_recv` methods.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// The channel is empty.
+    Empty,
+    /// The channel is empty and all senders are dropped.
+    Closed,
+}
+
+/// Data accessed only inside `PRIMASK` critical sections.
+struct Critical<T>(UnsafeCell<T>);
+
+unsafe impl<T: Send> Sync for Critical<T> {}
+
+/// Tasks waiting for a resource. All of them are woken on release, so that a
+/// dropped future can't swallow a wake-up.
+struct WaitList([Option<Waker>; WAIT_LIST_CAPACITY]);
+
+/// Fixed-capacity FIFO buffer.
+struct Ring<T, const N: usize> {
+    buf: MaybeUninit<[T; N]>,
+    head: usize,
+    len: usize,
+}
+
+impl<T> Critical<T> {
+    const fn new(value: T) -> Self {
+        Self(UnsafeCell::new(value))
+    }
+
+    /// Runs `f` with the data inside a critical section. `f` must not access
+    /// the same data.
+    fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        without_interrupts(|| f(unsafe { &mut *self.0.get() }))
+    }
+}
+
+impl WaitList {
+    const fn new() -> Self {
+        const NONE: Option<Waker> = None;
+        Self([NONE; WAIT_LIST_CAPACITY])
+    }
+
+    /// Registers `waker`. Returns `false` if the list is full, in which case
+    /// the task should be woken after the critical section.
+    fn register(&mut self, waker: &Waker) -> bool {
+        let mut vacant = None;
+        for slot in &mut self.0 {
+            if let Some(registered) = slot {
+                if registered.will_wake(waker) {
+                    return true;
+                }
+            } else if vacant.is_none() {
+                vacant = Some(slot);
+            }
+        }
+        if let Some(slot) = vacant {
+            *slot = Some(waker.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take(&mut self) -> Self {
+        mem::replace(self, Self::new())
+    }
+}
+
+impl<T, const N: usize> Ring<T, N> {
+    const fn new() -> Self {
+        Self { buf: MaybeUninit::uninit(), head: 0, len: 0 }
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    fn push(&mut self, value: T) {
+        debug_assert!(!self.is_full());
+        unsafe { self.slot((self.head + self.len) % N).write(value) };
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let value = unsafe { self.slot(self.head).read() };
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(value)
+    }
+
+    unsafe fn slot(&mut self, index: usize) -> *mut T {
+        unsafe { self.buf.as_mut_ptr().cast::<T>().add(index) }
+    }
+}
+
+impl<T, const N: usize> Drop for Ring<T, N> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+fn wake(waker: Option<Waker>) {
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+fn wake_all(wakers: WaitList) {
+    for waker in wakers.0.into_iter().flatten() {
+        waker.wake();
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sending on a closed channel.")
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => write!(f, "Sending on a full channel."),
+            Self::Closed(_) => write!(f, "Sending on a closed channel."),
+        }
+    }
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Receiving on an empty channel."),
+            Self::Closed => write!(f, "Receiving on an empty and closed channel."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_fifo() {
+        let mut ring = Ring::<u32, 3>::new();
+        assert_eq!(ring.pop(), None);
+        ring.push(1);
+        ring.push(2);
+        assert_eq!(ring.pop(), Some(1));
+        ring.push(3);
+        ring.push(4);
+        assert!(ring.is_full());
+        assert_eq!(ring.pop(), Some(2));
+        assert_eq!(ring.pop(), Some(3));
+        ring.push(5);
+        assert_eq!(ring.pop(), Some(4));
+        assert_eq!(ring.pop(), Some(5));
+        assert_eq!(ring.pop(), None);
+    }
+}
@@ -0,0 +1,203 @@
+//! A bounded multi-producer, single-consumer channel.
+
+use super::{wake, wake_all, Critical, Ring, SendError, TryRecvError, TrySendError, WaitList};
+use core::future::{poll_fn, Future};
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use futures::stream::{Next, Stream, StreamExt};
+
+/// A bounded multi-producer, single-consumer channel with capacity `N`.
+pub struct Channel<T, const N: usize> {
+    state: Critical<State<T, N>>,
+}
+
+/// The sending half of [`Channel`]. Can be cloned to send from multiple
+/// threads.
+pub struct Sender<'a, T, const N: usize> {
+    channel: &'a Channel<T, N>,
+}
+
+/// The receiving half of [`Channel`].
+pub struct Receiver<'a, T, const N: usize> {
+    channel: &'a Channel<T, N>,
+}
+
+struct State<T, const N: usize> {
+    ring: Ring<T, N>,
+    split: bool,
+    senders: usize,
+    receiver: bool,
+    rx_waker: Option<Waker>,
+    tx_wakers: WaitList,
+}
+
+impl<T, const N: usize> Channel<T, N> {
+    /// Creates a new channel.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is zero.
+    #[inline]
+    pub const fn new() -> Self {
+        assert!(N > 0, "channel capacity must be non-zero");
+        Self {
+            state: Critical::new(State {
+                ring: Ring::new(),
+                split: false,
+                senders: 0,
+                receiver: false,
+                rx_waker: None,
+                tx_wakers: WaitList::new(),
+            }),
+        }
+    }
+
+    /// Returns the sending and receiving halves of the channel.
+    ///
+    /// # Panics
+    ///
+    /// If the channel is already split.
+    pub fn split(&self) -> (Sender<'_, T, N>, Receiver<'_, T, N>) {
+        self.state.with(|state| {
+            assert!(!state.split, "channel is already split");
+            state.split = true;
+            state.senders = 1;
+            state.receiver = true;
+        });
+        (Sender { channel: self }, Receiver { channel: self })
+    }
+}
+
+impl<T, const N: usize> Default for Channel<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T, const N: usize> Sender<'a, T, N> {
+    /// Sends a value, waiting until there is capacity.
+    ///
+    /// # Errors
+    ///
+    /// If the receiver is dropped.
+    ///
+    /// # Panics
+    ///
+    /// If the future is polled after completion.
+    pub fn send(&mut self, value: T) -> impl Future<Output = Result<(), SendError<T>>> + 'a {
+        let channel = self.channel;
+        let mut value = Some(value);
+        poll_fn(move |cx| {
+            let mut registered = true;
+            let poll = channel.state.with(|state| {
+                if !state.receiver {
+                    return Poll::Ready(Err(SendError(value.take().unwrap())));
+                }
+                if state.ring.is_full() {
+                    registered = state.tx_wakers.register(cx.waker());
+                    return Poll::Pending;
+                }
+                state.ring.push(value.take().unwrap());
+                Poll::Ready(Ok(state.rx_waker.take()))
+            });
+            if !registered {
+                cx.waker().wake_by_ref();
+            }
+            poll.map(|result| result.map(wake))
+        })
+    }
+
+    /// Attempts to send a value immediately.
+    ///
+    /// # Errors
+    ///
+    /// If the channel is full, or the receiver is dropped.
+    pub fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        let waker = self.channel.state.with(|state| {
+            if !state.receiver {
+                return Err(TrySendError::Closed(value));
+            }
+            if state.ring.is_full() {
+                return Err(TrySendError::Full(value));
+            }
+            state.ring.push(value);
+            Ok(state.rx_waker.take())
+        })?;
+        wake(waker);
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> Clone for Sender<'_, T, N> {
+    fn clone(&self) -> Self {
+        self.channel.state.with(|state| state.senders += 1);
+        Self { channel: self.channel }
+    }
+}
+
+impl<T, const N: usize> Drop for Sender<'_, T, N> {
+    fn drop(&mut self) {
+        let waker = self.channel.state.with(|state| {
+            state.senders -= 1;
+            if state.senders == 0 { state.rx_waker.take() } else { None }
+        });
+        wake(waker);
+    }
+}
+
+impl<T, const N: usize> Receiver<'_, T, N> {
+    /// Receives a value, waiting until one is available. Returns `None` when
+    /// the channel is empty and all senders are dropped.
+    pub fn recv(&mut self) -> Next<'_, Self> {
+        self.next()
+    }
+
+    /// Attempts to receive a value immediately.
+    ///
+    /// # Errors
+    ///
+    /// If the channel is empty.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let (result, wakers) = self.channel.state.with(|state| match state.ring.pop() {
+            Some(value) => (Ok(value), state.tx_wakers.take()),
+            None if state.senders == 0 => (Err(TryRecvError::Closed), WaitList::new()),
+            None => (Err(TryRecvError::Empty), WaitList::new()),
+        });
+        wake_all(wakers);
+        result
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.try_recv() {
+            Ok(value) => Poll::Ready(Some(value)),
+            Err(TryRecvError::Closed) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => {
+                self.channel.state.with(|state| state.rx_waker = Some(cx.waker().clone()));
+                // A value could be sent before the waker is registered.
+                match self.try_recv() {
+                    Ok(value) => Poll::Ready(Some(value)),
+                    Err(TryRecvError::Closed) => Poll::Ready(None),
+                    Err(TryRecvError::Empty) => Poll::Pending,
+                }
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> Stream for Receiver<'_, T, N> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T, const N: usize> Drop for Receiver<'_, T, N> {
+    fn drop(&mut self) {
+        let wakers = self.channel.state.with(|state| {
+            state.receiver = false;
+            state.tx_wakers.take()
+        });
+        wake_all(wakers);
+    }
+}
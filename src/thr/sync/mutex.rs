@@ -0,0 +1,120 @@
+use super::{wake_all, Critical, WaitList};
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::future::{poll_fn, Future};
+use core::ops::{Deref, DerefMut};
+use core::task::Poll;
+
+/// An asynchronous mutual exclusion lock.
+///
+/// Unlike [`Resource`](crate::thr::Resource), the lock doesn't mask
+/// interrupts while the data is accessed. A thread trying to lock an already
+/// locked mutex waits until it is unlocked.
+pub struct Mutex<T> {
+    state: Critical<State>,
+    data: UnsafeCell<T>,
+}
+
+/// A guard providing access to the data of a locked [`Mutex`]. The mutex is
+/// unlocked when the guard is dropped.
+#[must_use = "if unused the Mutex will immediately unlock"]
+pub struct MutexGuard<'a, T> {
+    mutex: &'a Mutex<T>,
+}
+
+struct State {
+    locked: bool,
+    waiters: WaitList,
+}
+
+unsafe impl<T: Send> Sync for Mutex<T> {}
+unsafe impl<T: Sync> Sync for MutexGuard<'_, T> {}
+
+impl<T> Mutex<T> {
+    /// Creates a new unlocked mutex.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self {
+            state: Critical::new(State { locked: false, waiters: WaitList::new() }),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// Acquires the lock, waiting until it is available.
+    pub fn lock(&self) -> impl Future<Output = MutexGuard<'_, T>> {
+        poll_fn(move |cx| {
+            let mut registered = true;
+            let poll = self.state.with(|state| {
+                if state.locked {
+                    registered = state.waiters.register(cx.waker());
+                    Poll::Pending
+                } else {
+                    state.locked = true;
+                    Poll::Ready(MutexGuard { mutex: self })
+                }
+            });
+            if !registered {
+                cx.waker().wake_by_ref();
+            }
+            poll
+        })
+    }
+
+    /// Attempts to acquire the lock immediately.
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.state.with(|state| {
+            (!state.locked).then(|| {
+                state.locked = true;
+                MutexGuard { mutex: self }
+            })
+        })
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Consumes the mutex, returning the underlying data.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        let waiters = self.mutex.state.with(|state| {
+            state.locked = false;
+            state.waiters.take()
+        });
+        wake_all(waiters);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
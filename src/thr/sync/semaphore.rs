@@ -0,0 +1,87 @@
+use super::{wake_all, Critical, WaitList};
+use core::future::{poll_fn, Future};
+use core::mem;
+use core::task::Poll;
+
+/// An asynchronous counting semaphore.
+pub struct Semaphore {
+    state: Critical<State>,
+}
+
+/// A permit acquired from a [`Semaphore`]. The permit is returned to the
+/// semaphore when dropped.
+#[must_use = "if unused the permit will immediately be released"]
+pub struct SemaphorePermit<'a> {
+    semaphore: &'a Semaphore,
+}
+
+struct State {
+    permits: usize,
+    waiters: WaitList,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the number of available `permits`.
+    #[inline]
+    pub const fn new(permits: usize) -> Self {
+        Self { state: Critical::new(State { permits, waiters: WaitList::new() }) }
+    }
+
+    /// Acquires a permit, waiting until one is available.
+    pub fn acquire(&self) -> impl Future<Output = SemaphorePermit<'_>> {
+        poll_fn(move |cx| {
+            let mut registered = true;
+            let poll = self.state.with(|state| {
+                if state.permits == 0 {
+                    registered = state.waiters.register(cx.waker());
+                    Poll::Pending
+                } else {
+                    state.permits -= 1;
+                    Poll::Ready(SemaphorePermit { semaphore: self })
+                }
+            });
+            if !registered {
+                cx.waker().wake_by_ref();
+            }
+            poll
+        })
+    }
+
+    /// Attempts to acquire a permit immediately.
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.state.with(|state| {
+            (state.permits > 0).then(|| {
+                state.permits -= 1;
+                SemaphorePermit { semaphore: self }
+            })
+        })
+    }
+
+    /// Adds `n` new permits to the semaphore.
+    pub fn add_permits(&self, n: usize) {
+        let waiters = self.state.with(|state| {
+            state.permits += n;
+            state.waiters.take()
+        });
+        wake_all(waiters);
+    }
+
+    /// Returns the number of currently available permits.
+    pub fn available_permits(&self) -> usize {
+        self.state.with(|state| state.permits)
+    }
+}
+
+impl SemaphorePermit<'_> {
+    /// Consumes the permit without returning it to the semaphore.
+    #[inline]
+    pub fn forget(self) {
+        mem::forget(self);
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.semaphore.add_permits(1);
+    }
+}
@@ -0,0 +1,274 @@
+//! A bounded single-producer, single-consumer channel.
+//!
+//! Values are transferred with atomic loads and stores only. A critical section
+//! is entered only to register the waker of a blocked half, and to take it
+//! once the other half makes progress.
+
+use super::{wake, Critical, SendError, TryRecvError, TrySendError};
+use core::cell::UnsafeCell;
+use core::future::Future;
+use core::mem::MaybeUninit;
+use core::pin::Pin;
+use core::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+use core::task::{Context, Poll, Waker};
+use futures::stream::{Next, Stream, StreamExt};
+
+/// A bounded single-producer, single-consumer channel with capacity `N`.
+pub struct Channel<T, const N: usize> {
+    buf: UnsafeCell<MaybeUninit<[T; N]>>,
+    // Indices are in the range `0..2 * N` to distinguish full and empty states.
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    sender: AtomicBool,
+    receiver: AtomicBool,
+    // Set while the corresponding waker is registered, so that the other half
+    // can skip the critical section.
+    rx_waiting: AtomicBool,
+    tx_waiting: AtomicBool,
+    state: Critical<State>,
+}
+
+/// The sending half of [`Channel`].
+pub struct Sender<'a, T, const N: usize> {
+    channel: &'a Channel<T, N>,
+}
+
+/// The receiving half of [`Channel`].
+pub struct Receiver<'a, T, const N: usize> {
+    channel: &'a Channel<T, N>,
+}
+
+/// Future returned from [`Sender::send`].
+pub struct SendFuture<'s, 'a, T, const N: usize> {
+    sender: &'s mut Sender<'a, T, N>,
+    value: Option<T>,
+}
+
+struct State {
+    split: bool,
+    rx_waker: Option<Waker>,
+    tx_waker: Option<Waker>,
+}
+
+unsafe impl<T: Send, const N: usize> Sync for Channel<T, N> {}
+
+impl<T, const N: usize> Channel<T, N> {
+    /// Creates a new channel.
+    ///
+    /// # Panics
+    ///
+    /// If `N` is zero.
+    #[inline]
+    pub const fn new() -> Self {
+        assert!(N > 0, "channel capacity must be non-zero");
+        Self {
+            buf: UnsafeCell::new(MaybeUninit::uninit()),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            sender: AtomicBool::new(false),
+            receiver: AtomicBool::new(false),
+            rx_waiting: AtomicBool::new(false),
+            tx_waiting: AtomicBool::new(false),
+            state: Critical::new(State { split: false, rx_waker: None, tx_waker: None }),
+        }
+    }
+
+    /// Returns the sending and receiving halves of the channel.
+    ///
+    /// # Panics
+    ///
+    /// If the channel is already split.
+    pub fn split(&self) -> (Sender<'_, T, N>, Receiver<'_, T, N>) {
+        self.state.with(|state| {
+            assert!(!state.split, "channel is already split");
+            state.split = true;
+        });
+        self.sender.store(true, Ordering::Release);
+        self.receiver.store(true, Ordering::Release);
+        (Sender { channel: self }, Receiver { channel: self })
+    }
+
+    fn push(&self, value: T) -> Result<(), TrySendError<T>> {
+        if !self.receiver.load(Ordering::Acquire) {
+            return Err(TrySendError::Closed(value));
+        }
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Relaxed);
+        if (tail + 2 * N - head) % (2 * N) == N {
+            return Err(TrySendError::Full(value));
+        }
+        unsafe { self.slot(tail).write(value) };
+        self.tail.store(next(tail, N), Ordering::Release);
+        self.wake_rx();
+        Ok(())
+    }
+
+    fn pop(&self) -> Result<T, TryRecvError> {
+        // Values sent before the sender is dropped must be visible.
+        let closed = !self.sender.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        let head = self.head.load(Ordering::Relaxed);
+        if head == tail {
+            return Err(if closed { TryRecvError::Closed } else { TryRecvError::Empty });
+        }
+        let value = unsafe { self.slot(head).read() };
+        self.head.store(next(head, N), Ordering::Release);
+        self.wake_tx();
+        Ok(value)
+    }
+
+    fn register_rx(&self, waker: &Waker) {
+        self.state.with(|state| {
+            state.rx_waker = Some(waker.clone());
+            self.rx_waiting.store(true, Ordering::Relaxed);
+        });
+        // Pairs with the fence in `wake_rx`: either the following `pop` sees
+        // the sent value, or the sender sees the flag.
+        fence(Ordering::SeqCst);
+    }
+
+    fn register_tx(&self, waker: &Waker) {
+        self.state.with(|state| {
+            state.tx_waker = Some(waker.clone());
+            self.tx_waiting.store(true, Ordering::Relaxed);
+        });
+        // Pairs with the fence in `wake_tx`.
+        fence(Ordering::SeqCst);
+    }
+
+    fn wake_rx(&self) {
+        fence(Ordering::SeqCst);
+        if self.rx_waiting.load(Ordering::Relaxed) {
+            wake(self.state.with(|state| {
+                self.rx_waiting.store(false, Ordering::Relaxed);
+                state.rx_waker.take()
+            }));
+        }
+    }
+
+    fn wake_tx(&self) {
+        fence(Ordering::SeqCst);
+        if self.tx_waiting.load(Ordering::Relaxed) {
+            wake(self.state.with(|state| {
+                self.tx_waiting.store(false, Ordering::Relaxed);
+                state.tx_waker.take()
+            }));
+        }
+    }
+
+    unsafe fn slot(&self, index: usize) -> *mut T {
+        unsafe { (*self.buf.get()).as_mut_ptr().cast::<T>().add(index % N) }
+    }
+}
+
+impl<T, const N: usize> Default for Channel<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for Channel<T, N> {
+    fn drop(&mut self) {
+        while self.pop().is_ok() {}
+    }
+}
+
+impl<'a, T, const N: usize> Sender<'a, T, N> {
+    /// Sends a value, waiting until there is capacity.
+    ///
+    /// The future resolves to an error if the receiver is dropped.
+    pub fn send(&mut self, value: T) -> SendFuture<'_, 'a, T, N> {
+        SendFuture { sender: self, value: Some(value) }
+    }
+
+    /// Attempts to send a value immediately.
+    ///
+    /// # Errors
+    ///
+    /// If the channel is full, or the receiver is dropped.
+    pub fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        self.channel.push(value)
+    }
+}
+
+impl<T, const N: usize> Drop for Sender<'_, T, N> {
+    fn drop(&mut self) {
+        self.channel.sender.store(false, Ordering::Release);
+        self.channel.wake_rx();
+    }
+}
+
+impl<T, const N: usize> Unpin for SendFuture<'_, '_, T, N> {}
+
+impl<T, const N: usize> Future for SendFuture<'_, '_, T, N> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let channel = this.sender.channel;
+        let value = this.value.take().expect("polled after completion");
+        let value = match channel.push(value) {
+            Ok(()) => return Poll::Ready(Ok(())),
+            Err(TrySendError::Closed(value)) => return Poll::Ready(Err(SendError(value))),
+            Err(TrySendError::Full(value)) => value,
+        };
+        channel.register_tx(cx.waker());
+        // A value could be received before the waker is registered.
+        match channel.push(value) {
+            Ok(()) => Poll::Ready(Ok(())),
+            Err(TrySendError::Closed(value)) => Poll::Ready(Err(SendError(value))),
+            Err(TrySendError::Full(value)) => {
+                this.value = Some(value);
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> Receiver<'_, T, N> {
+    /// Receives a value, waiting until one is available. Returns `None` when
+    /// the channel is empty and the sender is dropped.
+    pub fn recv(&mut self) -> Next<'_, Self> {
+        self.next()
+    }
+
+    /// Attempts to receive a value immediately.
+    ///
+    /// # Errors
+    ///
+    /// If the channel is empty.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.channel.pop()
+    }
+}
+
+impl<T, const N: usize> Stream for Receiver<'_, T, N> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let channel = self.channel;
+        match channel.pop() {
+            Ok(value) => return Poll::Ready(Some(value)),
+            Err(TryRecvError::Closed) => return Poll::Ready(None),
+            Err(TryRecvError::Empty) => {}
+        }
+        channel.register_rx(cx.waker());
+        // A value could be sent before the waker is registered.
+        match channel.pop() {
+            Ok(value) => Poll::Ready(Some(value)),
+            Err(TryRecvError::Closed) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for Receiver<'_, T, N> {
+    fn drop(&mut self) {
+        self.channel.receiver.store(false, Ordering::Release);
+        self.channel.wake_tx();
+    }
+}
+
+fn next(index: usize, capacity: usize) -> usize {
+    if index + 1 == 2 * capacity { 0 } else { index + 1 }
+}
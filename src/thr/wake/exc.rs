@@ -0,0 +1,43 @@
+const SCB_ICSR: usize = 0xE000_ED04;
+
+macro_rules! wake_exc {
+    ($name:ident, $mod:ident, $bit:expr) => {
+        pub use self::$mod::$name;
+
+        mod $mod {
+            use super::SCB_ICSR;
+            use core::ptr::{self, write_volatile};
+            use core::task::{RawWaker, RawWakerVTable, Waker};
+
+            static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake, drop);
+
+            pub struct $name;
+
+            impl $name {
+                pub fn wakeup() {
+                    unsafe { write_volatile(SCB_ICSR as *mut u32, 1 << $bit) };
+                }
+
+                pub fn to_waker() -> Waker {
+                    unsafe { Waker::from_raw(raw_waker()) }
+                }
+            }
+
+            fn raw_waker() -> RawWaker {
+                RawWaker::new(ptr::null(), &VTABLE)
+            }
+
+            unsafe fn clone(_data: *const ()) -> RawWaker {
+                raw_waker()
+            }
+
+            unsafe fn wake(_data: *const ()) {
+                $name::wakeup();
+            }
+        }
+    };
+}
+
+wake_exc!(WakeNmi, nmi, 31);
+wake_exc!(WakePendSv, pend_sv, 28);
+wake_exc!(WakeSysTick, sys_tick, 26);
@@ -1,7 +1,10 @@
 use core::ptr::write_volatile;
 use core::task::{RawWaker, RawWakerVTable, Waker};
 
+#[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
 const NVIC_STIR: usize = 0xE000_EF00;
+#[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+const NVIC_ISPR: usize = 0xE000_E200;
 
 static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake, drop);
 
@@ -13,23 +16,51 @@ impl WakeInt {
         Self(int_num)
     }
 
+    #[cfg(not(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1")))]
     pub fn wakeup(&self) {
         unsafe { write_volatile(NVIC_STIR as *mut u32, u32::from(self.0)) };
     }
 
+    // ARMv6-M doesn't implement NVIC_STIR.
+    #[cfg(any(drone_cortexm = "cortexm0plus_r0p0", drone_cortexm = "cortexm0plus_r0p1"))]
+    pub fn wakeup(&self) {
+        let ispr = (NVIC_ISPR as *mut u32).wrapping_add(usize::from(self.0 >> 5));
+        unsafe { write_volatile(ispr, 1 << (self.0 & 0b1_1111)) };
+    }
+
     pub fn to_waker(&self) -> Waker {
         unsafe { Waker::from_raw(self.to_raw_waker()) }
     }
 
     fn to_raw_waker(&self) -> RawWaker {
-        RawWaker::new(self.0 as *const (), &VTABLE)
+        RawWaker::new(self.data(), &VTABLE)
+    }
+
+    fn data(&self) -> *const () {
+        usize::from(self.0) as *const ()
+    }
+
+    fn from_data(data: *const ()) -> Self {
+        Self::new(data as u16)
     }
 }
 
 unsafe fn clone(data: *const ()) -> RawWaker {
-    WakeInt::new(data as u16).to_raw_waker()
+    WakeInt::from_data(data).to_raw_waker()
 }
 
 unsafe fn wake(data: *const ()) {
-    WakeInt::new(data as u16).wakeup();
+    WakeInt::from_data(data).wakeup();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_round_trip() {
+        for int_num in [0, 1, 31, 32, 239, 479, u16::MAX] {
+            assert_eq!(WakeInt::from_data(WakeInt::new(int_num).data()).0, int_num);
+        }
+    }
 }
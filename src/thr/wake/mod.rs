@@ -1,5 +1,7 @@
+mod exc;
 mod int;
 mod root;
 
+pub use self::exc::{WakeNmi, WakePendSv, WakeSysTick};
 pub use self::int::WakeInt;
-pub use self::root::WakeRoot;
+pub use self::root::{WakeRoot, WakeRootTask, TASK_COUNT};
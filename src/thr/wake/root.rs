@@ -1,11 +1,28 @@
+use crate::drv::timer::Tickless;
 use crate::platform;
 use core::ptr;
+use core::sync::atomic::{AtomicBool, Ordering};
 use core::task::{RawWaker, RawWakerVTable, Waker};
 
+/// The maximum number of tasks with separate wake flags.
+pub const TASK_COUNT: usize = 32;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const FLAG: AtomicBool = AtomicBool::new(false);
+
+/// Wake flags of root tasks. Only loads and stores are used, so the flags
+/// work on ARMv6-M.
+static TASK_FLAGS: [AtomicBool; TASK_COUNT] = [FLAG; TASK_COUNT];
+
 static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake, drop);
 
+static TASK_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(task_clone, task_wake, task_wake, drop);
+
 pub struct WakeRoot(());
 
+pub struct WakeRootTask(usize);
+
 #[allow(clippy::unused_self)]
 impl WakeRoot {
     pub fn new() -> Self {
@@ -16,11 +33,46 @@ impl WakeRoot {
         platform::wait_for_event();
     }
 
+    pub fn wait_tickless<T: Tickless>(timer: &T) {
+        let ticks = timer.next_deadline().unwrap_or(u32::MAX);
+        if ticks > 1 && timer.stretch(ticks) {
+            platform::wait_for_event();
+            timer.unstretch();
+        } else {
+            platform::wait_for_event();
+        }
+    }
+
     pub fn to_waker(&self) -> Waker {
         unsafe { Waker::from_raw(raw_waker()) }
     }
 }
 
+impl WakeRootTask {
+    pub fn new(task: usize) -> Self {
+        assert!(task < TASK_COUNT);
+        Self(task)
+    }
+
+    pub fn wakeup(&self) {
+        TASK_FLAGS[self.0].store(true, Ordering::Release);
+        signal();
+    }
+
+    /// Clears the wake flag and returns its previous value.
+    pub fn take(&self) -> bool {
+        let woken = TASK_FLAGS[self.0].load(Ordering::Acquire);
+        if woken {
+            TASK_FLAGS[self.0].store(false, Ordering::Relaxed);
+        }
+        woken
+    }
+
+    pub fn to_waker(&self) -> Waker {
+        unsafe { Waker::from_raw(task_raw_waker(self.0)) }
+    }
+}
+
 fn raw_waker() -> RawWaker {
     RawWaker::new(ptr::null(), &VTABLE)
 }
@@ -30,6 +82,22 @@ unsafe fn clone(_data: *const ()) -> RawWaker {
 }
 
 unsafe fn wake(_data: *const ()) {
+    signal();
+}
+
+fn task_raw_waker(task: usize) -> RawWaker {
+    RawWaker::new(task as *const (), &TASK_VTABLE)
+}
+
+unsafe fn task_clone(data: *const ()) -> RawWaker {
+    task_raw_waker(data as usize)
+}
+
+unsafe fn task_wake(data: *const ()) {
+    WakeRootTask::new(data as usize).wakeup();
+}
+
+fn signal() {
     // In r0p0, r1p0, r1p1 and r2p0 versions of Cortex-M3 the event register is not
     // set for the exception entry, exception exit or debug events.
     #[cfg(any(
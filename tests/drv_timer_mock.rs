@@ -0,0 +1,57 @@
+#![cfg(feature = "host")]
+
+use core::num::NonZeroUsize;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use drone_cortexm::drv::timer::mock::{Call, MockTimer};
+use drone_cortexm::drv::timer::{Overflow, Timer};
+use futures::prelude::*;
+use futures::task::noop_waker_ref;
+
+#[test]
+fn interval() {
+    let mut timer = MockTimer::new();
+    let clock = timer.clock();
+    let mut cx = Context::from_waker(noop_waker_ref());
+    let mut interval = timer.interval(10);
+    assert!(Pin::new(&mut interval).poll_next(&mut cx).is_pending());
+    clock.advance(25);
+    assert!(matches!(
+        Pin::new(&mut interval).poll_next(&mut cx),
+        Poll::Ready(Some(Ok(pulses))) if pulses == NonZeroUsize::new(2).unwrap()
+    ));
+    clock.overflow();
+    assert!(matches!(
+        Pin::new(&mut interval).poll_next(&mut cx),
+        Poll::Ready(Some(Err(Overflow)))
+    ));
+    assert!(Pin::new(&mut interval).poll_next(&mut cx).is_pending());
+    drop(interval);
+    assert_eq!(clock.calls(), [Call::Interval(10), Call::Stop]);
+}
+
+#[test]
+fn interval_reload() {
+    let mut timer = MockTimer::new();
+    let clock = timer.clock();
+    let mut cx = Context::from_waker(noop_waker_ref());
+    let mut interval = timer.interval_skip(10);
+    clock.advance(5);
+    Pin::new(&mut interval).set_period(20);
+    clock.advance(5);
+    assert_eq!(Pin::new(&mut interval).poll_next(&mut cx), Poll::Ready(NonZeroUsize::new(1)));
+    clock.advance(19);
+    assert!(Pin::new(&mut interval).poll_next(&mut cx).is_pending());
+    Pin::new(&mut interval).pause();
+    clock.advance(100);
+    assert!(Pin::new(&mut interval).poll_next(&mut cx).is_pending());
+    Pin::new(&mut interval).resume();
+    clock.advance(1);
+    assert_eq!(Pin::new(&mut interval).poll_next(&mut cx), Poll::Ready(NonZeroUsize::new(1)));
+    assert_eq!(clock.take_calls(), [
+        Call::IntervalSkip(10),
+        Call::SetPeriod(20),
+        Call::Pause,
+        Call::Resume
+    ]);
+}
@@ -2,10 +2,11 @@
 #![no_implicit_prelude]
 
 use ::drone_cortexm::sv::{Supervisor, SvService};
-use ::drone_cortexm::thr::Thread;
+use ::drone_cortexm::thr::{ExcToken, Exception, Thread, VectorMap};
 use ::drone_cortexm::{sv, thr};
 use ::std::assert_eq;
 use ::std::mem::size_of;
+use ::std::option::Option::{None, Some};
 
 struct FooService;
 
@@ -37,6 +38,8 @@ thr::nvic! {
 
     supervisor => Sv;
 
+    default => unhandled_int;
+
     threads => {
         exceptions => {
             /// Test doc attribute
@@ -64,6 +67,8 @@ fn nmi_handler(_thr: &Thr) {}
 
 extern "C" fn rcc_handler() {}
 
+fn unhandled_int(_int_num: u16) {}
+
 sv::pool! {
     pool => pub SERVICES;
     supervisor => pub Sv;
@@ -87,3 +92,18 @@ fn size() {
     assert_eq!(size_of::<Vectors>(), 208);
     assert_eq!(SERVICES.len(), 2);
 }
+
+#[test]
+fn exc_token() {
+    assert_eq!(<<Nmi as ExcToken>::Exc as Exception>::EXC_NUM, 2);
+    assert_eq!(<<SysTick as ExcToken>::Exc as Exception>::EXC_NUM, 15);
+}
+
+#[test]
+fn vector_map() {
+    assert_eq!(Thr::thr_idx(2), Some(0));
+    assert_eq!(Thr::thr_idx(15), Some(1));
+    assert_eq!(Thr::thr_idx(26), Some(2));
+    assert_eq!(Thr::thr_idx(11), None);
+    assert_eq!(Thr::thr_idx(21), None);
+}
@@ -0,0 +1,78 @@
+use drone_cortexm::thr;
+use drone_cortexm::thr::info::{self, VectorInfo, VectorKind};
+use drone_cortexm::thr::{Priority, Resource, ThrBudget, ThrPriority, ThrsInitToken};
+
+thr::nvic! {
+    thread => pub Thr {};
+
+    #[allow(dead_code)]
+    local => pub ThrLocal {};
+
+    #[allow(dead_code)]
+    index => pub Thrs;
+
+    vectors => pub Vectors;
+
+    init => pub ThrsInit;
+
+    info => pub VECTORS_INFO;
+
+    storm => {
+        window => sys_tick(100);
+        hook => storm_hook;
+    };
+
+    budget => budget_hook;
+
+    threads => {
+        exceptions => {
+            pub sys_tick(priority = 3, budget = 1000);
+        };
+        interrupts => {
+            10: pub exti4(priority = 1, enabled, rate = 50);
+            5: pub naked(rcc_handler) rcc;
+        };
+    };
+}
+
+extern "C" fn rcc_handler() {}
+
+fn storm_hook(_int_num: u16) {}
+
+fn budget_hook(_thr_idx: u16, _cycles: u32) {}
+
+#[test]
+fn thr_config() {
+    assert_eq!(<ThrsInit as ThrsInitToken>::THR_CONFIG.len(), 2);
+}
+
+#[test]
+fn resource_ceiling() {
+    assert_eq!(<SysTick as ThrPriority>::PRIORITY, Priority::from_level(3));
+    assert_eq!(Resource::<(), (SysTick,)>::CEILING, Priority::from_level(3));
+    assert_eq!(Resource::<(), (SysTick, Exti4)>::CEILING, Priority::from_level(1));
+}
+
+#[test]
+fn budget_meter() {
+    assert_eq!(SysTick::BUDGET, 1000);
+    let meter = SysTick::meter();
+    unsafe {
+        assert!(!meter.record(800, SysTick::BUDGET));
+        assert!(meter.record(1200, SysTick::BUDGET));
+        assert!(!meter.record(1000, SysTick::BUDGET));
+    }
+    assert_eq!(meter.overruns(), 1);
+    assert_eq!(meter.max_cycles(), 1200);
+}
+
+#[test]
+fn vectors_info() {
+    let vectors = info::parse(&VECTORS_INFO).unwrap().collect::<Vec<_>>();
+    assert_eq!(vectors, [
+        VectorInfo { number: 15, name: "sys_tick", kind: VectorKind::Inner, priority: Some(3) },
+        VectorInfo { number: 21, name: "rcc", kind: VectorKind::Naked, priority: None },
+        VectorInfo { number: 26, name: "exti4", kind: VectorKind::Inner, priority: Some(1) },
+    ]);
+    assert_eq!(info::parse(&VECTORS_INFO[..10]).err(), Some(info::InfoError::Truncated));
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "host")]
+
+use core::cell::Cell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use drone_cortexm::thr::root::Executor;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+// Only one executor can exist at a time.
+static EXECUTOR: Mutex<()> = Mutex::new(());
+
+/// A future, which wakes itself and yields `count` times before completion.
+struct Yield<'a> {
+    count: usize,
+    polls: &'a Cell<usize>,
+}
+
+impl Future for Yield<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        self.polls.set(self.polls.get() + 1);
+        if self.count == 0 {
+            return Poll::Ready(());
+        }
+        self.count -= 1;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+fn lock() -> MutexGuard<'static, ()> {
+    EXECUTOR.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[test]
+fn run() {
+    let _lock = lock();
+    let (a_polls, b_polls) = (Cell::new(0), Cell::new(0));
+    let mut executor = Executor::new();
+    executor.spawn(Yield { count: 3, polls: &a_polls });
+    executor.spawn(Yield { count: 0, polls: &b_polls });
+    assert_eq!(executor.len(), 2);
+    executor.run();
+    assert!(executor.is_empty());
+    // Only woken tasks are polled.
+    assert_eq!((a_polls.get(), b_polls.get()), (4, 1));
+}
+
+#[test]
+fn spawn_full() {
+    let _lock = lock();
+    let polls = Cell::new(0);
+    let mut executor = Executor::new();
+    for _ in 0..32 {
+        executor.spawn(Yield { count: 1, polls: &polls });
+    }
+    assert_eq!(executor.len(), 32);
+    executor.run();
+    assert_eq!(polls.get(), 64);
+    // Slots of complete tasks are reused.
+    for _ in 0..32 {
+        executor.spawn(Yield { count: 0, polls: &polls });
+    }
+    executor.run();
+    assert_eq!(polls.get(), 96);
+}
+
+#[test]
+#[should_panic(expected = "root executor is full")]
+fn spawn_overflow() {
+    let _lock = lock();
+    let polls = Cell::new(0);
+    let mut executor = Executor::new();
+    for _ in 0..33 {
+        executor.spawn(Yield { count: 0, polls: &polls });
+    }
+}
+
+#[test]
+#[should_panic(expected = "root executor already exists")]
+fn single_executor() {
+    let _lock = lock();
+    let _executor = Executor::new();
+    let _ = Executor::new();
+}
@@ -0,0 +1,72 @@
+use drone_cortexm::thr;
+use drone_cortexm::thr::{
+    Priority, SoftToken, SoftVector, Thread, ThrPriority, ThrsInitToken, VectorMap,
+};
+use std::mem::size_of;
+
+thr::nvic! {
+    thread => pub Thr {};
+
+    #[allow(dead_code)]
+    local => pub ThrLocal {};
+
+    #[allow(dead_code)]
+    index => pub Thrs;
+
+    vectors => pub Vectors;
+
+    init => pub ThrsInit;
+
+    threads => {
+        exceptions => {
+            pub sys_tick;
+        };
+        software(pend_sv, priority = 7) => {
+            pub logger;
+            pub sensors(priority = 0);
+            pub housekeeping(priority = 2);
+        };
+        software(3) => {
+            pub flash(priority = 1);
+            pub outer(storage_handler) storage(priority = 1);
+        };
+    };
+}
+
+fn storage_handler(_thr: &Thr) {}
+
+#[test]
+fn size() {
+    assert_eq!(Thr::COUNT, 6);
+    assert_eq!(size_of::<Vectors>(), 152);
+}
+
+#[test]
+fn soft_rank() {
+    assert_eq!(Sensors::SOFT_RANK, 0);
+    assert_eq!(Housekeeping::SOFT_RANK, 1);
+    assert_eq!(Logger::SOFT_RANK, 2);
+    assert_eq!(Flash::SOFT_RANK, 0);
+    assert_eq!(Storage::SOFT_RANK, 1);
+    assert_eq!(Logger::soft_thread().rank(), 2);
+    assert_eq!(Storage::soft_thread().rank(), 1);
+}
+
+#[test]
+fn soft_priority() {
+    assert_eq!(<Logger as ThrPriority>::PRIORITY, Priority::from_level(7));
+    assert_eq!(<Sensors as ThrPriority>::PRIORITY, Priority::from_level(7));
+}
+
+#[test]
+fn soft_vector() {
+    assert_eq!(THR_SOFT_DISPATCHER_0.vector(), SoftVector::PendSv);
+    assert_eq!(THR_SOFT_DISPATCHER_1.vector(), SoftVector::Int(3));
+    assert_eq!(Thr::thr_idx(14), None);
+    assert_eq!(Thr::thr_idx(19), None);
+}
+
+#[test]
+fn thr_config() {
+    assert_eq!(<ThrsInit as ThrsInitToken>::THR_CONFIG.len(), 2);
+}
@@ -0,0 +1,284 @@
+#![cfg(feature = "host")]
+
+use core::pin::pin;
+use core::ptr;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use drone_cortexm::thr::sync::{
+    mpsc, spsc, Mutex, Semaphore, TryRecvError, TrySendError, WAIT_LIST_CAPACITY,
+};
+use futures::prelude::*;
+use futures::task::{self, noop_waker_ref, ArcWake};
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+static RACE_VTABLE: RawWakerVTable = RawWakerVTable::new(race_clone, drop, drop, drop);
+
+thread_local! {
+    static RACE_TX: RefCell<Option<spsc::Sender<'static, u32, 1>>> = const { RefCell::new(None) };
+}
+
+#[derive(Default)]
+struct Counter(AtomicUsize);
+
+struct Unpark(Thread);
+
+impl ArcWake for Counter {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl ArcWake for Unpark {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.unpark();
+    }
+}
+
+impl Counter {
+    fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Returns a waker, which sends a value through `RACE_TX` when it is cloned
+/// for registration.
+fn race_waker() -> Waker {
+    unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &RACE_VTABLE)) }
+}
+
+unsafe fn race_clone(_data: *const ()) -> RawWaker {
+    if let Some(mut tx) = RACE_TX.with(|tx| tx.borrow_mut().take()) {
+        tx.try_send(1).unwrap();
+    }
+    RawWaker::new(ptr::null(), &RACE_VTABLE)
+}
+
+/// Runs `future` to completion on the current thread. Panics if a wake-up is
+/// missed.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = task::waker(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        let start = Instant::now();
+        thread::park_timeout(TIMEOUT);
+        assert!(start.elapsed() < TIMEOUT, "missed wake-up");
+    }
+}
+
+#[test]
+fn spsc_transitions() {
+    let channel = spsc::Channel::<u32, 2>::new();
+    let (mut tx, mut rx) = channel.split();
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(tx.try_send(1), Ok(()));
+    assert_eq!(tx.try_send(2), Ok(()));
+    assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+    assert_eq!(rx.try_recv(), Ok(1));
+    assert_eq!(tx.try_send(3), Ok(()));
+    drop(tx);
+    assert_eq!(rx.try_recv(), Ok(2));
+    assert_eq!(rx.try_recv(), Ok(3));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+}
+
+#[test]
+fn spsc_receiver_closed() {
+    let channel = spsc::Channel::<u32, 2>::new();
+    let (mut tx, rx) = channel.split();
+    drop(rx);
+    assert_eq!(tx.try_send(1), Err(TrySendError::Closed(1)));
+}
+
+#[test]
+fn spsc_wake() {
+    let counter = Arc::new(Counter::default());
+    let waker = task::waker(Arc::clone(&counter));
+    let mut cx = Context::from_waker(&waker);
+    let channel = spsc::Channel::<u32, 1>::new();
+    let (mut tx, mut rx) = channel.split();
+    assert!(rx.poll_next_unpin(&mut cx).is_pending());
+    assert_eq!(tx.try_send(1), Ok(()));
+    assert_eq!(counter.count(), 1);
+    let mut send = tx.send(2);
+    assert!(send.poll_unpin(&mut cx).is_pending());
+    assert_eq!(rx.try_recv(), Ok(1));
+    assert_eq!(counter.count(), 2);
+    assert_eq!(send.poll_unpin(&mut cx), Poll::Ready(Ok(())));
+    // The wakers are taken, so no more wake-ups.
+    assert_eq!(rx.try_recv(), Ok(2));
+    assert_eq!(counter.count(), 2);
+}
+
+#[test]
+fn spsc_register_race() {
+    static CHANNEL: spsc::Channel<u32, 1> = spsc::Channel::new();
+    let (tx, mut rx) = CHANNEL.split();
+    RACE_TX.with(|race_tx| *race_tx.borrow_mut() = Some(tx));
+    let waker = race_waker();
+    let mut cx = Context::from_waker(&waker);
+    // The value is sent after the channel is found empty, but before the waker
+    // is registered.
+    assert_eq!(rx.poll_next_unpin(&mut cx), Poll::Ready(Some(1)));
+}
+
+#[test]
+fn spsc_race() {
+    const COUNT: u32 = 100_000;
+    static CHANNEL: spsc::Channel<u32, 1> = spsc::Channel::new();
+    let (mut tx, mut rx) = CHANNEL.split();
+    let sender = thread::spawn(move || {
+        block_on(async {
+            for i in 0..COUNT {
+                tx.send(i).await.unwrap();
+            }
+        });
+    });
+    block_on(async {
+        for i in 0..COUNT {
+            assert_eq!(rx.recv().await, Some(i));
+        }
+        assert_eq!(rx.recv().await, None);
+    });
+    sender.join().unwrap();
+}
+
+#[test]
+fn mpsc_transitions() {
+    let channel = mpsc::Channel::<u32, 2>::new();
+    let (mut tx1, mut rx) = channel.split();
+    let mut tx2 = tx1.clone();
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(tx1.try_send(1), Ok(()));
+    assert_eq!(tx2.try_send(2), Ok(()));
+    assert_eq!(tx2.try_send(3), Err(TrySendError::Full(3)));
+    assert_eq!(rx.try_recv(), Ok(1));
+    drop(tx1);
+    assert_eq!(rx.try_recv(), Ok(2));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    drop(tx2);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+}
+
+#[test]
+fn mpsc_receiver_closed() {
+    let channel = mpsc::Channel::<u32, 2>::new();
+    let (mut tx, rx) = channel.split();
+    drop(rx);
+    assert_eq!(tx.try_send(1), Err(TrySendError::Closed(1)));
+    let mut cx = Context::from_waker(noop_waker_ref());
+    assert!(matches!(pin!(tx.send(2)).poll(&mut cx), Poll::Ready(Err(_))));
+}
+
+#[test]
+fn mpsc_wake() {
+    let counter = Arc::new(Counter::default());
+    let waker = task::waker(Arc::clone(&counter));
+    let mut cx = Context::from_waker(&waker);
+    let channel = mpsc::Channel::<u32, 1>::new();
+    let (mut tx1, mut rx) = channel.split();
+    let mut tx2 = tx1.clone();
+    assert!(rx.poll_next_unpin(&mut cx).is_pending());
+    assert_eq!(tx1.try_send(1), Ok(()));
+    assert_eq!(counter.count(), 1);
+    let mut send1 = pin!(tx1.send(2));
+    let mut send2 = pin!(tx2.send(3));
+    assert!(send1.as_mut().poll(&mut cx).is_pending());
+    assert!(send2.as_mut().poll(&mut cx).is_pending());
+    // Both senders registered the same waker, which is woken once.
+    assert_eq!(rx.try_recv(), Ok(1));
+    assert_eq!(counter.count(), 2);
+    assert_eq!(send1.as_mut().poll(&mut cx), Poll::Ready(Ok(())));
+    assert!(send2.as_mut().poll(&mut cx).is_pending());
+}
+
+#[test]
+fn mpsc_race() {
+    const COUNT: u32 = 50_000;
+    static CHANNEL: mpsc::Channel<u32, 1> = mpsc::Channel::new();
+    let (tx, rx) = CHANNEL.split();
+    let senders = (0..2)
+        .map(|_| {
+            let mut tx = tx.clone();
+            thread::spawn(move || {
+                block_on(async {
+                    for i in 0..COUNT {
+                        tx.send(i).await.unwrap();
+                    }
+                });
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(tx);
+    let sum = block_on(rx.fold(0_u64, |sum, i| async move { sum + u64::from(i) }));
+    assert_eq!(sum, u64::from(COUNT) * u64::from(COUNT - 1));
+    for sender in senders {
+        sender.join().unwrap();
+    }
+}
+
+#[test]
+fn mutex_wait() {
+    let counter = Arc::new(Counter::default());
+    let waker = task::waker(Arc::clone(&counter));
+    let mut cx = Context::from_waker(&waker);
+    let mutex = Mutex::new(0);
+    let mut guard = mutex.try_lock().unwrap();
+    assert!(mutex.try_lock().is_none());
+    let mut lock = pin!(mutex.lock());
+    assert!(lock.as_mut().poll(&mut cx).is_pending());
+    *guard += 1;
+    drop(guard);
+    assert_eq!(counter.count(), 1);
+    let Poll::Ready(guard) = lock.as_mut().poll(&mut cx) else { panic!("mutex is not acquired") };
+    assert_eq!(*guard, 1);
+}
+
+#[test]
+fn mutex_wait_list_full() {
+    let counters = (0..=WAIT_LIST_CAPACITY).map(|_| Arc::new(Counter::default()));
+    let counters = counters.collect::<Vec<_>>();
+    let mutex = Mutex::new(());
+    let guard = mutex.try_lock().unwrap();
+    for counter in &counters {
+        let waker = task::waker(Arc::clone(counter));
+        let mut cx = Context::from_waker(&waker);
+        assert!(pin!(mutex.lock()).poll(&mut cx).is_pending());
+    }
+    let (registered, excess) = counters.split_at(WAIT_LIST_CAPACITY);
+    // The excess task is woken right away to poll again.
+    assert!(registered.iter().all(|counter| counter.count() == 0));
+    assert_eq!(excess[0].count(), 1);
+    drop(guard);
+    assert!(registered.iter().all(|counter| counter.count() == 1));
+}
+
+#[test]
+fn semaphore_permits() {
+    let counter = Arc::new(Counter::default());
+    let waker = task::waker(Arc::clone(&counter));
+    let mut cx = Context::from_waker(&waker);
+    let semaphore = Semaphore::new(1);
+    let permit = semaphore.try_acquire().unwrap();
+    assert_eq!(semaphore.available_permits(), 0);
+    assert!(semaphore.try_acquire().is_none());
+    let mut acquire = pin!(semaphore.acquire());
+    assert!(acquire.as_mut().poll(&mut cx).is_pending());
+    drop(permit);
+    assert_eq!(counter.count(), 1);
+    let Poll::Ready(permit) = acquire.as_mut().poll(&mut cx) else {
+        panic!("permit is not acquired")
+    };
+    permit.forget();
+    assert_eq!(semaphore.available_permits(), 0);
+    semaphore.add_permits(2);
+    assert_eq!(semaphore.available_permits(), 2);
+}